@@ -69,6 +69,7 @@ struct Message {
     username: String,
     message: String,
     #[serde(skip)]
+    #[tql(auto_now_add)]
     date_added: DateTime<Utc>,
 }
 
@@ -88,8 +89,7 @@ fn chat(req: &mut Request) -> IronResult<Response> {
                 // Insert a new message.
                 let _ = sql!(Message.insert(
                             username = username,
-                            message = message,
-                            date_added = Utc::now()
+                            message = message
                         ));
             }
         }
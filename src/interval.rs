@@ -0,0 +1,125 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Storage of `chrono::Duration` in an `INTERVAL` column with PostgreSQL and in an integer column
+//! with SQLite.
+
+use chrono::Duration;
+
+use Convert;
+
+#[doc(hidden)]
+// The wrapper of a `Duration`, whose value is a number of microseconds.
+#[derive(Debug)]
+pub struct Interval(pub Duration);
+
+impl Convert for Duration {
+    type Wrapper = Interval;
+
+    fn to_wrapper(&self) -> Interval {
+        Interval(*self)
+    }
+
+    fn from_wrapper(wrapper: Interval) -> Self {
+        wrapper.0
+    }
+}
+
+const TOO_LARGE: &str = "the duration is too large to be stored in microseconds";
+
+#[cfg(feature = "postgres")]
+mod pg {
+    use std::error::Error;
+
+    use chrono::Duration;
+    use postgres::types::{FromSql, INTERVAL, IsNull, ToSql, Type, __to_sql_checked};
+
+    use super::{Interval, TOO_LARGE};
+
+    // NOTE: the binary format of INTERVAL is the microseconds, the days and the months.
+    const INTERVAL_SIZE: usize = 16;
+
+    impl ToSql for Interval {
+        fn to_sql(&self, _ty: &Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+            let microseconds = self.0.num_microseconds().ok_or(TOO_LARGE)?;
+            out.extend_from_slice(&microseconds.to_be_bytes());
+            out.extend_from_slice(&0i32.to_be_bytes());
+            out.extend_from_slice(&0i32.to_be_bytes());
+            Ok(IsNull::No)
+        }
+
+        fn accepts(ty: &Type) -> bool {
+            *ty == INTERVAL
+        }
+
+        fn to_sql_checked(&self, ty: &Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+            __to_sql_checked(self, ty, out)
+        }
+    }
+
+    impl FromSql for Interval {
+        fn from_sql(_ty: &Type, raw: &[u8]) -> Result<Self, Box<Error + Sync + Send>> {
+            if raw.len() != INTERVAL_SIZE {
+                return Err("invalid INTERVAL value".into());
+            }
+            let microseconds = read_big_endian(&raw[..8]);
+            let days = read_big_endian(&raw[8..12]) as i32;
+            let months = read_big_endian(&raw[12..]) as i32;
+            // NOTE: the length of a month depends on the date it is added to.
+            if months != 0 {
+                return Err("an interval with months cannot be converted to a Duration".into());
+            }
+            Ok(Interval(Duration::microseconds(microseconds) + Duration::days(i64::from(days))))
+        }
+
+        fn accepts(ty: &Type) -> bool {
+            *ty == INTERVAL
+        }
+    }
+
+    fn read_big_endian(bytes: &[u8]) -> i64 {
+        bytes.iter().fold(0, |value, &byte| value << 8 | i64::from(byte))
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+mod sqlite {
+    use chrono::Duration;
+    use rusqlite::{Error, Result};
+    use rusqlite::types::{FromSql, FromSqlResult, ToSql, ToSqlOutput, Value, ValueRef};
+
+    use super::{Interval, TOO_LARGE};
+
+    impl ToSql for Interval {
+        fn to_sql(&self) -> Result<ToSqlOutput> {
+            let microseconds = self.0.num_microseconds()
+                .ok_or_else(|| Error::ToSqlConversionFailure(TOO_LARGE.into()))?;
+            Ok(ToSqlOutput::Owned(Value::Integer(microseconds)))
+        }
+    }
+
+    impl FromSql for Interval {
+        fn column_result(value: ValueRef) -> FromSqlResult<Self> {
+            value.as_i64()
+                .map(|microseconds| Interval(Duration::microseconds(microseconds)))
+        }
+    }
+}
@@ -0,0 +1,119 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Storage of serde types as JSON documents.
+
+use std::ops::{Deref, DerefMut};
+
+/// A value stored as a JSON document: `JSONB` with PostgreSQL and `TEXT` with SQLite.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Json<T>(pub T);
+
+impl<T> Deref for Json<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Json<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "postgres")]
+mod pg {
+    use std::error::Error;
+    use std::io::Write;
+
+    use postgres::types::{FromSql, IsNull, JSON, JSONB, ToSql, Type, __to_sql_checked};
+    use serde::Serialize;
+    use serde::de::DeserializeOwned;
+    use serde_json;
+
+    use super::Json;
+
+    // NOTE: the binary format of JSONB is its text format prefixed by a version number.
+    const JSONB_VERSION: u8 = 1;
+
+    impl<T: Serialize> ToSql for Json<T> {
+        fn to_sql(&self, ty: &Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+            if *ty == JSONB {
+                out.push(JSONB_VERSION);
+            }
+            serde_json::to_writer(out.by_ref(), &self.0)?;
+            Ok(IsNull::No)
+        }
+
+        fn accepts(ty: &Type) -> bool {
+            *ty == JSON || *ty == JSONB
+        }
+
+        fn to_sql_checked(&self, ty: &Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+            __to_sql_checked(self, ty, out)
+        }
+    }
+
+    impl<T: DeserializeOwned> FromSql for Json<T> {
+        fn from_sql(ty: &Type, mut raw: &[u8]) -> Result<Self, Box<Error + Sync + Send>> {
+            if *ty == JSONB {
+                if raw.first() != Some(&JSONB_VERSION) {
+                    return Err("unsupported JSONB encoding version".into());
+                }
+                raw = &raw[1..];
+            }
+            Ok(Json(serde_json::from_slice(raw)?))
+        }
+
+        fn accepts(ty: &Type) -> bool {
+            *ty == JSON || *ty == JSONB
+        }
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+mod sqlite {
+    use rusqlite::{Error, Result};
+    use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, Value, ValueRef};
+    use serde::Serialize;
+    use serde::de::DeserializeOwned;
+    use serde_json;
+
+    use super::Json;
+
+    impl<T: Serialize> ToSql for Json<T> {
+        fn to_sql(&self) -> Result<ToSqlOutput> {
+            let json = serde_json::to_string(&self.0)
+                .map_err(|error| Error::ToSqlConversionFailure(Box::new(error)))?;
+            Ok(ToSqlOutput::Owned(Value::Text(json)))
+        }
+    }
+
+    impl<T: DeserializeOwned> FromSql for Json<T> {
+        fn column_result(value: ValueRef) -> FromSqlResult<Self> {
+            serde_json::from_str(value.as_str()?)
+                .map(Json)
+                .map_err(|error| FromSqlError::Other(Box::new(error)))
+        }
+    }
+}
@@ -29,22 +29,112 @@ extern crate chrono;
 extern crate postgres;
 #[cfg(feature = "rusqlite")]
 extern crate rusqlite;
+#[cfg(feature = "decimal")]
+extern crate rust_decimal;
+#[cfg(feature = "json")]
+extern crate serde;
+#[cfg(feature = "json")]
+extern crate serde_json;
+#[cfg(feature = "uuid")]
+extern crate uuid;
 
 pub mod aggregates;
+#[cfg(feature = "chrono")]
+mod interval;
+#[cfg(feature = "json")]
+mod json;
 mod methods;
+mod net;
 mod types;
+mod unsigned;
+mod validation;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::error::Error;
+use std::hash::Hash;
 
+#[cfg(feature = "chrono")]
+#[doc(hidden)]
+pub use interval::Interval;
+#[cfg(feature = "json")]
+pub use json::Json;
+#[doc(hidden)]
+pub use net::Inet;
 pub use types::{Date, DateTime, Time, ToTqlType};
-use types::StdI32;
+use types::{StdI32, StdU8};
 pub use types::numbers::{i16, i32, i64, i8, u16, u32, u64, u8};
+pub use unsigned::{RangeError, Unsigned};
+#[doc(hidden)]
+pub use unsigned::Signed;
+pub use validation::ValidationError;
+#[doc(hidden)]
+pub use validation::{StringValue, check_max_length};
 
 /// The `ForeignKey` is optional.
 ///
 /// There is no value when the `join()` method is not called.
-pub type ForeignKey<T> = Option<T>;
+/// The related item is boxed so that a table can reference itself, hence every related item is a
+/// `Box<T>`, even when the key is not self-referential.
+pub type ForeignKey<T> = Option<Box<T>>;
 
-/// A `PrimaryKey` is a 4-byte integer.
-pub type PrimaryKey = StdI32;
+/// The `ManyToMany` related items are stored in a separate table.
+///
+/// It is empty when the `join()` method is not called.
+pub type ManyToMany<T> = Vec<T>;
+
+/// A `PrimaryKey` is a 4-byte integer by default.
+///
+/// Use `PrimaryKey<i64>` for an 8-byte integer or another type (like `String`) for a natural key.
+/// Only the integer primary keys are generated by the database.
+pub type PrimaryKey<T = StdI32> = T;
+
+/// A user-defined type stored in a column as one of the types supported by tql.
+///
+/// Use `#[derive(SqlType)]` with `#[tql(transparent)]` to implement it on a single-field struct, or
+/// with `#[tql(repr = "…")]` to implement it manually.
+pub trait SqlType: Sized {
+    /// The supported type used to store the value.
+    type Repr;
+
+    fn to_repr(&self) -> Self::Repr;
+
+    /// Convert the value read from the database, which can be invalid (e.g. an unknown variant of a
+    /// `SqlEnum`): the error is returned by the conversion of the backend.
+    fn from_repr(repr: Self::Repr) -> Result<Self, Box<Error + Sync + Send>>;
+}
+
+#[doc(hidden)]
+// The wrapper of a field whose type is a type parameter, which is read as the representation of its
+// SqlType.
+pub struct Repr<T>(pub T);
+
+#[cfg(feature = "postgres")]
+impl<T: SqlType> ::postgres::types::FromSql for Repr<T>
+where T::Repr: ::postgres::types::FromSql,
+{
+    fn from_sql(ty: &::postgres::types::Type, raw: &[StdU8]) -> Result<Self, Box<Error + Sync + Send>> {
+        let repr = T::Repr::from_sql(ty, raw)?;
+        Ok(Repr(T::from_repr(repr)?))
+    }
+
+    fn accepts(ty: &::postgres::types::Type) -> bool {
+        T::Repr::accepts(ty)
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+impl<T: SqlType> ::rusqlite::types::FromSql for Repr<T>
+where T::Repr: ::rusqlite::types::FromSql,
+{
+    fn column_result(value: ::rusqlite::types::ValueRef) -> ::rusqlite::types::FromSqlResult<Self> {
+        let repr = T::Repr::column_result(value)?;
+        T::from_repr(repr)
+            .map(Repr)
+            .map_err(::rusqlite::types::FromSqlError::Other)
+    }
+}
 
 #[doc(hidden)]
 // Marker trait used for error reporting:
@@ -55,8 +145,16 @@ pub unsafe trait SqlTable {
     #[cfg(feature = "rusqlite")]
     const FIELD_COUNT: StdI32;
 
+    /// The type of the primary key, a tuple when it has multiple columns.
+    type PrimaryKey;
+
     fn _tql_default() -> Self;
 
+    /// Merge the rows of the same item, which are returned by a join on a `ManyToMany` field.
+    fn merge_rows(items: Vec<Self>) -> Vec<Self> where Self: Sized;
+
+    fn primary_key(&self) -> Self::PrimaryKey;
+
     #[cfg(feature = "postgres")]
     fn from_row(row: &::postgres::rows::Row) -> Self;
 
@@ -68,22 +166,190 @@ pub unsafe trait SqlTable {
 
     #[cfg(feature = "rusqlite")]
     fn from_related_row(row: &::rusqlite::Row, delta: StdI32) -> Self;
+
+    /// Check if the related row is missing, which happens with a left join on a nullable `ForeignKey`.
+    #[cfg(feature = "postgres")]
+    fn is_related_row_null(row: &::postgres::rows::Row, delta: usize) -> bool;
+
+    #[cfg(feature = "rusqlite")]
+    fn is_related_row_null(row: &::rusqlite::Row, delta: StdI32) -> bool;
+
+    #[cfg(feature = "postgres")]
+    fn primary_key_from_row(row: &::postgres::rows::Row) -> Self::PrimaryKey;
+
+    #[cfg(feature = "rusqlite")]
+    fn primary_key_from_row(row: &::rusqlite::Row) -> Self::PrimaryKey;
+}
+
+#[doc(hidden)]
+// A struct with #[derive(SqlEmbed)], whose fields are stored in the columns of the tables having a
+// field of this type with #[tql(embed)].
+pub trait SqlEmbed {
+    #[cfg(feature = "postgres")]
+    const FIELD_COUNT: usize;
+    #[cfg(feature = "rusqlite")]
+    const FIELD_COUNT: StdI32;
+
+    /// Read the fields from the columns starting at `delta`.
+    #[cfg(feature = "postgres")]
+    fn from_row(row: &::postgres::rows::Row, delta: usize) -> Self;
+
+    #[cfg(feature = "rusqlite")]
+    fn from_row(row: &::rusqlite::Row, delta: StdI32) -> Self;
+}
+
+#[doc(hidden)]
+// A field type which is not converted by the database libraries, so it is sent and read through a
+// wrapper type.
+pub trait Convert: Sized {
+    type Wrapper;
+
+    fn to_wrapper(&self) -> Self::Wrapper;
+
+    fn from_wrapper(wrapper: Self::Wrapper) -> Self;
+}
+
+impl<T: Convert> Convert for Option<T> {
+    type Wrapper = Option<T::Wrapper>;
+
+    fn to_wrapper(&self) -> Self::Wrapper {
+        self.as_ref().map(Convert::to_wrapper)
+    }
+
+    fn from_wrapper(wrapper: Self::Wrapper) -> Self {
+        wrapper.map(T::from_wrapper)
+    }
+}
+
+impl<'a> Convert for Cow<'a, str> {
+    type Wrapper = String;
+
+    fn to_wrapper(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_wrapper(wrapper: String) -> Self {
+        Cow::Owned(wrapper)
+    }
+}
+
+#[doc(hidden)]
+// A field filled by the join() method.
+pub trait RelatedField {
+    type Table: SqlTable;
+
+    fn set_related(&mut self, related: Self::Table);
+}
+
+impl<T: SqlTable> RelatedField for ForeignKey<T> {
+    type Table = T;
+
+    fn set_related(&mut self, related: T) {
+        *self = Some(Box::new(related));
+    }
+}
+
+impl<T: SqlTable> RelatedField for Option<ForeignKey<T>> {
+    type Table = T;
+
+    fn set_related(&mut self, related: T) {
+        *self = Some(Some(Box::new(related)));
+    }
+}
+
+impl<T: SqlTable> RelatedField for ManyToMany<T> {
+    type Table = T;
+
+    fn set_related(&mut self, related: T) {
+        self.push(related);
+    }
 }
 
 #[cfg(feature = "postgres")]
 #[doc(hidden)]
-pub fn from_related_row<T: SqlTable>(field: &mut Option<T>, row: &::postgres::rows::Row, delta: usize) -> usize
+pub fn from_related_row<F: RelatedField>(field: &mut F, row: &::postgres::rows::Row, delta: usize) -> usize
+{
+    if !F::Table::is_related_row_null(row, delta) {
+        field.set_related(F::Table::from_related_row(row, delta));
+    }
+    F::Table::FIELD_COUNT
+}
+
+#[cfg(feature = "rusqlite")]
+#[doc(hidden)]
+pub fn from_related_row<F: RelatedField>(field: &mut F, row: &::rusqlite::Row, delta: StdI32) -> StdI32
 {
-    *field = Some(T::from_related_row(row, delta));
-    T::FIELD_COUNT
+    if !F::Table::is_related_row_null(row, delta) {
+        field.set_related(F::Table::from_related_row(row, delta));
+    }
+    F::Table::FIELD_COUNT
 }
 
 #[cfg(feature = "rusqlite")]
 #[doc(hidden)]
-pub fn from_related_row<T: SqlTable>(field: &mut Option<T>, row: &::rusqlite::Row, delta: StdI32) -> StdI32
+// Convert the representation of a SqlType to an owned value, because the representation does not
+// outlive the conversion.
+pub fn to_sql_output<T>(repr: &T) -> ::rusqlite::Result<::rusqlite::types::ToSqlOutput<'static>>
+where T: ::rusqlite::types::ToSql,
 {
-    *field = Some(T::from_related_row(row, delta));
-    T::FIELD_COUNT
+    use rusqlite::types::ToSqlOutput;
+
+    match repr.to_sql()? {
+        ToSqlOutput::Borrowed(value) => Ok(ToSqlOutput::Owned(value.into())),
+        ToSqlOutput::Owned(value) => Ok(ToSqlOutput::Owned(value)),
+        #[allow(unreachable_patterns)]
+        _ => unimplemented!("a SqlType cannot be represented as a zero blob"),
+    }
+}
+
+#[cfg(feature = "uuid")]
+#[doc(hidden)]
+// Generate the value of a `Uuid` primary key which is not given to insert().
+pub fn generate_uuid() -> ::uuid::Uuid {
+    ::uuid::Uuid::new_v4()
+}
+
+#[doc(hidden)]
+// Get the primary key of an item of a related field to type check an argument.
+pub fn related_primary_key<F: RelatedField>(_field: &mut F) -> &mut <F::Table as SqlTable>::PrimaryKey {
+    unimplemented!()
+}
+
+#[doc(hidden)]
+// A join on a ManyToMany field returns a row for each related item: merge the rows of the items
+// having the same primary key with the `merge` function.
+pub fn merge_rows<T, M>(items: Vec<T>, merge: M) -> Vec<T>
+where T: SqlTable,
+      T::PrimaryKey: Eq + Hash,
+      M: Fn(&mut T, T),
+{
+    let mut indexes = HashMap::new();
+    let mut result: Vec<T> = vec![];
+    for item in items {
+        match indexes.entry(item.primary_key()) {
+            Entry::Occupied(entry) => merge(&mut result[*entry.get()], item),
+            Entry::Vacant(entry) => {
+                entry.insert(result.len());
+                result.push(item);
+            },
+        }
+    }
+    result
+}
+
+#[doc(hidden)]
+// Add the related items which are not already in the ManyToMany field.
+// NOTE: a join on multiple ManyToMany fields returns the same related item in multiple rows.
+pub fn merge_related<T>(field: &mut ManyToMany<T>, related_items: ManyToMany<T>)
+where T: SqlTable,
+      T::PrimaryKey: PartialEq,
+{
+    for related_item in related_items {
+        let primary_key = related_item.primary_key();
+        if !field.iter().any(|item| item.primary_key() == primary_key) {
+            field.push(related_item);
+        }
+    }
 }
 
 // Stable implementation.
@@ -101,6 +367,19 @@ macro_rules! check_missing_fields {
     }};
 }
 
+#[cfg(not(unstable))]
+#[macro_export]
+macro_rules! check_max_length {
+    ($($tt:tt)*) => {{
+        #[derive(StableCheckMaxLength)]
+        enum __TqlStableCheckMaxLengthEnum {
+            Input = (stringify!($($tt)*), 0).1,
+        }
+
+        __tql_call_macro_max_length!()
+    }};
+}
+
 #[cfg(not(unstable))]
 #[macro_export]
 macro_rules! sql {
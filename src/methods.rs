@@ -22,7 +22,13 @@
 //! These methods should not be used directly:
 //! they exist only for type checking.
 
-use types::{Date, DateTime, Time, TqlOption, TqlString};
+use types::{Date, DateTime, Time, ToTqlType, TqlArray, TqlIpAddr, TqlJson, TqlOption, TqlString};
+
+impl<T: ToTqlType> TqlArray<T> {
+    pub fn contains_elem<U: ToTqlType<Target = T::Target>>(&self, _element: U) -> bool { false }
+    pub fn len(&self) -> i32 { 0 }
+    pub fn overlaps(&self, _array: &[T]) -> bool { false }
+}
 
 impl Date {
     pub fn day(&self) -> i32 { 0 }
@@ -55,6 +61,16 @@ impl TqlString {
     pub fn starts_with(&self, _string: &str) -> bool { false }
 }
 
+impl TqlIpAddr {
+    pub fn in_subnet(&self, _subnet: &str) -> bool { false }
+}
+
+impl TqlJson {
+    pub fn contains_json<T: ToTqlType<Target = TqlJson>>(&self, _value: &T) -> bool { false }
+    pub fn get(&self, _key: &str) -> &'static str { "" }
+    pub fn has_key(&self, _key: &str) -> bool { false }
+}
+
 impl TqlOption {
     pub fn is_some(&self) -> bool { false }
     pub fn is_none(&self) -> bool { false }
@@ -0,0 +1,134 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Storage of `std::net::IpAddr` in an `INET` column with PostgreSQL and in a `TEXT` column with
+//! SQLite.
+
+use std::net::IpAddr;
+
+use Convert;
+
+#[doc(hidden)]
+// The wrapper of an `IpAddr`.
+#[derive(Debug)]
+pub struct Inet(pub IpAddr);
+
+impl Convert for IpAddr {
+    type Wrapper = Inet;
+
+    fn to_wrapper(&self) -> Inet {
+        Inet(*self)
+    }
+
+    fn from_wrapper(wrapper: Inet) -> Self {
+        wrapper.0
+    }
+}
+
+#[cfg(feature = "postgres")]
+mod pg {
+    use std::error::Error;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    use postgres::types::{FromSql, INET, IsNull, ToSql, Type, __to_sql_checked};
+
+    use super::Inet;
+
+    // NOTE: the binary format of INET is the address family, the number of bits of the netmask, the
+    // CIDR flag, the length of the address and the address.
+    const AF_INET: u8 = 2;
+    const AF_INET6: u8 = 3;
+    const HEADER_SIZE: usize = 4;
+
+    impl ToSql for Inet {
+        fn to_sql(&self, _ty: &Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+            match self.0 {
+                IpAddr::V4(address) => {
+                    out.extend_from_slice(&[AF_INET, 32, 0, 4]);
+                    out.extend_from_slice(&address.octets());
+                },
+                IpAddr::V6(address) => {
+                    out.extend_from_slice(&[AF_INET6, 128, 0, 16]);
+                    out.extend_from_slice(&address.octets());
+                },
+            }
+            Ok(IsNull::No)
+        }
+
+        fn accepts(ty: &Type) -> bool {
+            *ty == INET
+        }
+
+        fn to_sql_checked(&self, ty: &Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+            __to_sql_checked(self, ty, out)
+        }
+    }
+
+    impl FromSql for Inet {
+        // NOTE: the netmask of the value is ignored.
+        fn from_sql(_ty: &Type, raw: &[u8]) -> Result<Self, Box<Error + Sync + Send>> {
+            if raw.len() < HEADER_SIZE {
+                return Err("invalid INET value".into());
+            }
+            let address = &raw[HEADER_SIZE..];
+            match (raw[0], address.len()) {
+                (AF_INET, 4) => {
+                    let mut octets = [0; 4];
+                    octets.copy_from_slice(address);
+                    Ok(Inet(IpAddr::V4(Ipv4Addr::from(octets))))
+                },
+                (AF_INET6, 16) => {
+                    let mut octets = [0; 16];
+                    octets.copy_from_slice(address);
+                    Ok(Inet(IpAddr::V6(Ipv6Addr::from(octets))))
+                },
+                _ => Err("invalid INET value".into()),
+            }
+        }
+
+        fn accepts(ty: &Type) -> bool {
+            *ty == INET
+        }
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+mod sqlite {
+    use rusqlite::Result;
+    use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, Value, ValueRef};
+
+    use super::Inet;
+
+    impl ToSql for Inet {
+        fn to_sql(&self) -> Result<ToSqlOutput> {
+            Ok(ToSqlOutput::Owned(Value::Text(self.0.to_string())))
+        }
+    }
+
+    impl FromSql for Inet {
+        fn column_result(value: ValueRef) -> FromSqlResult<Self> {
+            value.as_str()?
+                .parse()
+                .map(Inet)
+                .map_err(|error| FromSqlError::Other(Box::new(error)))
+        }
+    }
+}
@@ -24,8 +24,20 @@
 
 #![allow(dead_code, non_camel_case_types)]
 
+use std::marker::PhantomData;
+use std::net::IpAddr;
+
 #[cfg(feature = "chrono")]
 use chrono::{self, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+#[cfg(feature = "decimal")]
+use rust_decimal;
+#[cfg(feature = "json")]
+use serde_json;
+#[cfg(feature = "uuid")]
+use uuid;
+
+#[cfg(feature = "json")]
+use json::Json;
 
 type StdI8 = i8;
 
@@ -119,7 +131,7 @@ pub type StdI32 = i32;
 
 type StdI64 = i64;
 
-type StdU8 = u8;
+pub type StdU8 = u8;
 
 type StdU16 = u16;
 
@@ -131,6 +143,14 @@ type StdF32 = f32;
 
 type StdF64 = f64;
 
+#[doc(hidden)]
+pub struct TqlArray<T>(PhantomData<T>);
+
+impl<T> ToTqlType for Vec<T> {
+    type Target = TqlArray<T>;
+    fn to_tql_type(&self) -> Self::Target { TqlArray(PhantomData) }
+}
+
 #[doc(hidden)]
 pub struct Date;
 
@@ -182,6 +202,38 @@ impl ToTqlType for NaiveTime {
     fn to_tql_type(&self) -> Self::Target { Time }
 }
 
+#[doc(hidden)]
+pub struct TqlDecimal;
+
+#[cfg(feature = "decimal")]
+impl ToTqlType for rust_decimal::Decimal {
+    type Target = TqlDecimal;
+    fn to_tql_type(&self) -> Self::Target { TqlDecimal }
+}
+
+#[doc(hidden)]
+pub struct TqlIpAddr;
+
+impl ToTqlType for IpAddr {
+    type Target = TqlIpAddr;
+    fn to_tql_type(&self) -> Self::Target { TqlIpAddr }
+}
+
+#[doc(hidden)]
+pub struct TqlJson;
+
+#[cfg(feature = "json")]
+impl<T> ToTqlType for Json<T> {
+    type Target = TqlJson;
+    fn to_tql_type(&self) -> Self::Target { TqlJson }
+}
+
+#[cfg(feature = "json")]
+impl ToTqlType for serde_json::Value {
+    type Target = TqlJson;
+    fn to_tql_type(&self) -> Self::Target { TqlJson }
+}
+
 #[doc(hidden)]
 pub struct TqlOption;
 
@@ -198,8 +250,27 @@ impl ToTqlType for String {
     fn to_tql_type(&self) -> Self::Target { TqlString }
 }
 
+impl<'a> ToTqlType for &'a str {
+    type Target = TqlString;
+    fn to_tql_type(&self) -> Self::Target { TqlString }
+}
+
+#[doc(hidden)]
+pub struct TqlUuid;
+
+#[cfg(feature = "uuid")]
+impl ToTqlType for uuid::Uuid {
+    type Target = TqlUuid;
+    fn to_tql_type(&self) -> Self::Target { TqlUuid }
+}
+
 pub trait ToTqlType {
     type Target;
 
     fn to_tql_type(&self) -> Self::Target;
 }
+
+impl<'a, T: ToTqlType> ToTqlType for &'a T {
+    type Target = T::Target;
+    fn to_tql_type(&self) -> Self::Target { (**self).to_tql_type() }
+}
@@ -0,0 +1,247 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Storage of the unsigned integers in the next wider signed type, with range checks instead of
+//! wrapping.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::{i32, i64, u16, u32};
+
+use Convert;
+
+/// An unsigned integer stored in a signed column.
+///
+/// A `u16` is stored as an `i32` and a `u32` as an `i64`, while a `u64` is stored as an `i64` and
+/// cannot exceed `i64::MAX`.
+pub trait Unsigned: Sized {
+    /// The signed type used to store the value.
+    type Signed;
+
+    fn to_signed(&self) -> Result<Self::Signed, RangeError>;
+
+    fn from_signed(signed: Self::Signed) -> Result<Self, RangeError>;
+}
+
+/// The error returned when an integer cannot be converted to or from its column type.
+#[derive(Debug)]
+pub struct RangeError {
+    typ: &'static str,
+    value: String,
+}
+
+impl RangeError {
+    fn new<T: Display>(value: T, typ: &'static str) -> Self {
+        RangeError {
+            typ,
+            value: value.to_string(),
+        }
+    }
+}
+
+impl Display for RangeError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "the value {} is out of range for the type {}", self.value, self.typ)
+    }
+}
+
+impl Error for RangeError {
+    fn description(&self) -> &str {
+        "integer out of range"
+    }
+}
+
+impl Unsigned for u16 {
+    type Signed = i32;
+
+    fn to_signed(&self) -> Result<i32, RangeError> {
+        Ok(i32::from(*self))
+    }
+
+    fn from_signed(signed: i32) -> Result<Self, RangeError> {
+        if signed < 0 || signed > i32::from(u16::MAX) {
+            return Err(RangeError::new(signed, "u16"));
+        }
+        Ok(signed as u16)
+    }
+}
+
+impl Unsigned for u32 {
+    type Signed = i64;
+
+    fn to_signed(&self) -> Result<i64, RangeError> {
+        Ok(i64::from(*self))
+    }
+
+    fn from_signed(signed: i64) -> Result<Self, RangeError> {
+        if signed < 0 || signed > i64::from(u32::MAX) {
+            return Err(RangeError::new(signed, "u32"));
+        }
+        Ok(signed as u32)
+    }
+}
+
+impl Unsigned for u64 {
+    type Signed = i64;
+
+    fn to_signed(&self) -> Result<i64, RangeError> {
+        if *self > i64::MAX as u64 {
+            return Err(RangeError::new(self, "i64"));
+        }
+        Ok(*self as i64)
+    }
+
+    fn from_signed(signed: i64) -> Result<Self, RangeError> {
+        if signed < 0 {
+            return Err(RangeError::new(signed, "u64"));
+        }
+        Ok(signed as u64)
+    }
+}
+
+impl<T: Unsigned> Unsigned for Option<T> {
+    type Signed = Option<T::Signed>;
+
+    fn to_signed(&self) -> Result<Self::Signed, RangeError> {
+        match *self {
+            Some(ref value) => value.to_signed().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn from_signed(signed: Self::Signed) -> Result<Self, RangeError> {
+        match signed {
+            Some(signed) => T::from_signed(signed).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+#[doc(hidden)]
+// The wrapper of an unsigned integer, which is converted to its signed type when it is sent to the
+// database and checked when it is read.
+#[derive(Debug)]
+pub struct Signed<T>(pub T);
+
+impl Convert for u16 {
+    type Wrapper = Signed<u16>;
+
+    fn to_wrapper(&self) -> Signed<u16> {
+        Signed(*self)
+    }
+
+    fn from_wrapper(wrapper: Signed<u16>) -> Self {
+        wrapper.0
+    }
+}
+
+impl Convert for u32 {
+    type Wrapper = Signed<u32>;
+
+    fn to_wrapper(&self) -> Signed<u32> {
+        Signed(*self)
+    }
+
+    fn from_wrapper(wrapper: Signed<u32>) -> Self {
+        wrapper.0
+    }
+}
+
+impl Convert for u64 {
+    type Wrapper = Signed<u64>;
+
+    fn to_wrapper(&self) -> Signed<u64> {
+        Signed(*self)
+    }
+
+    fn from_wrapper(wrapper: Signed<u64>) -> Self {
+        wrapper.0
+    }
+}
+
+#[cfg(feature = "postgres")]
+mod pg {
+    use std::error::Error;
+    use std::fmt::Debug;
+
+    use postgres::types::{FromSql, IsNull, ToSql, Type, __to_sql_checked};
+
+    use super::{Signed, Unsigned};
+
+    impl<T: Debug + Unsigned> ToSql for Signed<T>
+    where T::Signed: ToSql,
+    {
+        fn to_sql(&self, ty: &Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+            self.0.to_signed()?.to_sql(ty, out)
+        }
+
+        fn accepts(ty: &Type) -> bool {
+            T::Signed::accepts(ty)
+        }
+
+        fn to_sql_checked(&self, ty: &Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+            __to_sql_checked(self, ty, out)
+        }
+    }
+
+    impl<T: Unsigned> FromSql for Signed<T>
+    where T::Signed: FromSql,
+    {
+        fn from_sql(ty: &Type, raw: &[u8]) -> Result<Self, Box<Error + Sync + Send>> {
+            let signed = T::Signed::from_sql(ty, raw)?;
+            Ok(Signed(T::from_signed(signed)?))
+        }
+
+        fn accepts(ty: &Type) -> bool {
+            T::Signed::accepts(ty)
+        }
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+mod sqlite {
+    use rusqlite::{Error, Result};
+    use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+
+    use super::{Signed, Unsigned};
+    use to_sql_output;
+
+    impl<T: Unsigned> ToSql for Signed<T>
+    where T::Signed: ToSql,
+    {
+        fn to_sql(&self) -> Result<ToSqlOutput> {
+            let signed = self.0.to_signed()
+                .map_err(|error| Error::ToSqlConversionFailure(Box::new(error)))?;
+            to_sql_output(&signed)
+        }
+    }
+
+    impl<T: Unsigned> FromSql for Signed<T>
+    where T::Signed: FromSql,
+    {
+        fn column_result(value: ValueRef) -> FromSqlResult<Self> {
+            let signed = T::Signed::column_result(value)?;
+            T::from_signed(signed)
+                .map(Signed)
+                .map_err(|error| FromSqlError::Other(Box::new(error)))
+        }
+    }
+}
@@ -0,0 +1,144 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+#[cfg(feature = "postgres")]
+use std::io;
+
+/// The error returned by an insert or an update when a value assigned to a field is longer than
+/// its `#[tql(max_length = …)]` or is rejected by its `#[tql(validate = "…")]` function.
+///
+/// The query is not sent to the database in this case: the error of the backend wraps this error,
+/// which is retrieved with `ValidationError::from_error()`.
+#[derive(Debug)]
+pub struct ValidationError {
+    field: &'static str,
+    message: String,
+}
+
+impl ValidationError {
+    #[doc(hidden)]
+    pub fn new<M: Into<String>>(field: &'static str, message: M) -> Self {
+        ValidationError {
+            field,
+            message: message.into(),
+        }
+    }
+
+    /// The name of the field whose value is invalid.
+    pub fn field(&self) -> &'static str {
+        self.field
+    }
+
+    /// The message returned by the validation function.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "invalid value for the field {}: {}", self.field, self.message)
+    }
+}
+
+impl Error for ValidationError {
+    fn description(&self) -> &str {
+        "invalid field value"
+    }
+}
+
+#[doc(hidden)]
+// The value of a field with #[tql(max_length = …)], which can be nullable.
+pub trait StringValue {
+    fn as_str_value(&self) -> Option<&str>;
+}
+
+impl StringValue for String {
+    fn as_str_value(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl<'a> StringValue for &'a str {
+    fn as_str_value(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl<'a> StringValue for Cow<'a, str> {
+    fn as_str_value(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl<T: StringValue> StringValue for Option<T> {
+    fn as_str_value(&self) -> Option<&str> {
+        self.as_ref().and_then(StringValue::as_str_value)
+    }
+}
+
+#[doc(hidden)]
+// Check that the value does not have more characters than the max_length of its column.
+pub fn check_max_length<T: StringValue>(value: &T, max_length: usize) -> Result<(), String> {
+    match value.as_str_value().map(|string| string.chars().count()) {
+        Some(length) if length > max_length =>
+            Err(format!("the length ({}) is greater than the max_length ({})", length, max_length)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl ValidationError {
+    /// Get the validation error wrapped in an error returned by a query, if any.
+    pub fn from_error(error: &::postgres::Error) -> Option<&ValidationError> {
+        error.as_io()
+            .and_then(|error| error.get_ref())
+            .and_then(|error| error.downcast_ref())
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+impl ValidationError {
+    /// Get the validation error wrapped in an error returned by a query, if any.
+    pub fn from_error(error: &::rusqlite::Error) -> Option<&ValidationError> {
+        match *error {
+            ::rusqlite::Error::ToSqlConversionFailure(ref error) => error.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<ValidationError> for ::postgres::Error {
+    fn from(error: ValidationError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, error).into()
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+impl From<ValidationError> for ::rusqlite::Error {
+    fn from(error: ValidationError) -> Self {
+        ::rusqlite::Error::ToSqlConversionFailure(Box::new(error))
+    }
+}
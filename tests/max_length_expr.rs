@@ -0,0 +1,122 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+#[macro_use]
+extern crate lazy_static;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+#[macro_use]
+mod connection;
+mod teardown;
+
+backend_extern_crate!();
+
+use std::sync::Mutex;
+
+use tql::{PrimaryKey, ValidationError};
+use tql_macros::sql;
+
+use connection::{Connection, get_connection};
+use teardown::TearDown;
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct TableMaxLengthExpr {
+    id: PrimaryKey,
+    #[tql(max_length = 5)]
+    field1: String,
+    #[tql(max_length = 5)]
+    field2: Option<String>,
+}
+
+lazy_static! {
+    static ref LOCK: Mutex<Connection> = Mutex::new(get_connection());
+}
+
+#[test]
+fn test_insert() {
+    let connection = LOCK.lock().unwrap();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableMaxLengthExpr.drop());
+    });
+
+    let _ = sql!(TableMaxLengthExpr.create());
+
+    let field2: Option<String> = None;
+    let id = sql!(TableMaxLengthExpr.insert(field1 = "abcde", field2 = field2)).unwrap();
+
+    let table = sql!(TableMaxLengthExpr.get(id)).unwrap();
+    assert_eq!("abcde", table.field1);
+    assert_eq!(None, table.field2);
+
+    let field1 = "ééééé".to_string();
+    let id = sql!(TableMaxLengthExpr.insert(field1 = field1)).unwrap();
+
+    let table = sql!(TableMaxLengthExpr.get(id)).unwrap();
+    assert_eq!("ééééé", table.field1);
+
+    let field1 = "abcdef".to_string();
+    let error = sql!(TableMaxLengthExpr.insert(field1 = field1)).unwrap_err();
+    let validation_error = ValidationError::from_error(&error).expect("validation error");
+    assert_eq!("field1", validation_error.field());
+    assert_eq!("the length (6) is greater than the max_length (5)", validation_error.message());
+
+    let field2 = Some("abcdef".to_string());
+    let error = sql!(TableMaxLengthExpr.insert(field1 = "abcde", field2 = field2)).unwrap_err();
+    assert_eq!("field2", ValidationError::from_error(&error).expect("validation error").field());
+
+    let tables = sql!(TableMaxLengthExpr.all()).unwrap();
+    assert_eq!(2, tables.len());
+}
+
+#[test]
+fn test_update() {
+    let connection = LOCK.lock().unwrap();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableMaxLengthExpr.drop());
+    });
+
+    let _ = sql!(TableMaxLengthExpr.create());
+
+    let field2 = Some("bio".to_string());
+    let id = sql!(TableMaxLengthExpr.insert(field1 = "abcde", field2 = field2)).unwrap();
+
+    let field1 = "abcdef".to_string();
+    let error = sql!(TableMaxLengthExpr.get(id).update(field1 = field1)).unwrap_err();
+    assert_eq!("field1", ValidationError::from_error(&error).expect("validation error").field());
+
+    let table = sql!(TableMaxLengthExpr.get(id)).unwrap();
+    assert_eq!("abcde", table.field1);
+    assert_eq!(Some("bio".to_string()), table.field2);
+
+    let field2: Option<String> = None;
+    let num_updated = sql!(TableMaxLengthExpr.get(id).update(field2 = field2)).unwrap();
+    assert_eq!(1, num_updated);
+
+    let table = sql!(TableMaxLengthExpr.get(id)).unwrap();
+    assert_eq!(None, table.field2);
+}
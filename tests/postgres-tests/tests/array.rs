@@ -0,0 +1,86 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate postgres;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use tql::PrimaryKey;
+use tql_macros::to_sql;
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Post {
+    id: PrimaryKey,
+    title: String,
+    tags: Vec<String>,
+    scores: Vec<i32>,
+    ratings: Option<Vec<f64>>,
+}
+
+const SELECT: &str = "SELECT Post.id, Post.title, Post.tags, Post.scores, Post.ratings";
+
+#[test]
+fn test_create() {
+    assert_eq!(
+        "CREATE TABLE Post (id SERIAL PRIMARY KEY NOT NULL, title CHARACTER VARYING NOT NULL, tags CHARACTER VARYING[] NOT NULL, scores INTEGER[] NOT NULL, ratings DOUBLE PRECISION[])",
+        to_sql!(Post.create())
+    );
+}
+
+#[test]
+fn test_select() {
+    assert_eq!(
+        format!("{} FROM Post WHERE 'rust' = ANY(tags)", SELECT),
+        to_sql!(Post.filter(tags.contains_elem("rust")))
+    );
+    assert_eq!(
+        format!("{} FROM Post WHERE $1 = ANY(scores)", SELECT),
+        to_sql!(Post.filter(scores.contains_elem(&score)))
+    );
+    assert_eq!(
+        format!("{} FROM Post WHERE tags && $1", SELECT),
+        to_sql!(Post.filter(tags.overlaps(&other_tags)))
+    );
+    assert_eq!(
+        format!("{} FROM Post WHERE CARDINALITY(tags) > 2", SELECT),
+        to_sql!(Post.filter(tags.len() > 2))
+    );
+    assert_eq!(
+        format!("{} FROM Post WHERE LENGTH(title) > 2", SELECT),
+        to_sql!(Post.filter(title.len() > 2))
+    );
+    assert_eq!(
+        format!("{} FROM Post WHERE Post.title = $1 AND CARDINALITY(scores) = $2", SELECT),
+        to_sql!(Post.filter(title == title && scores.len() == count))
+    );
+}
+
+#[test]
+fn test_insert() {
+    assert_eq!(
+        "INSERT INTO Post(title, tags, scores) VALUES($1, $2, $3) RETURNING id",
+        to_sql!(Post.insert(title = title, tags = tags, scores = scores))
+    );
+}
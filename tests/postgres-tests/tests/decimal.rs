@@ -0,0 +1,68 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate postgres;
+extern crate rust_decimal;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use tql::PrimaryKey;
+use tql_macros::to_sql;
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Invoice {
+    id: PrimaryKey,
+    #[tql(precision = 12, scale = 2)]
+    total: rust_decimal::Decimal,
+    #[tql(precision = 5)]
+    quantity: Option<rust_decimal::Decimal>,
+    rate: rust_decimal::Decimal,
+}
+
+const SELECT: &str = "SELECT Invoice.id, Invoice.total, Invoice.quantity, Invoice.rate";
+
+#[test]
+fn test_create() {
+    assert_eq!(
+        "CREATE TABLE Invoice (id SERIAL PRIMARY KEY NOT NULL, total NUMERIC(12,2) NOT NULL, quantity NUMERIC(5,0), rate NUMERIC NOT NULL)",
+        to_sql!(Invoice.create())
+    );
+}
+
+#[test]
+fn test_select() {
+    assert_eq!(
+        format!("{} FROM Invoice WHERE Invoice.total >= $1 AND Invoice.rate < $2", SELECT),
+        to_sql!(Invoice.filter(total >= minimum && rate < rate))
+    );
+}
+
+#[test]
+fn test_update() {
+    assert_eq!(
+        "UPDATE Invoice SET total = total + $1, rate = rate * $2 WHERE Invoice.id = $3",
+        to_sql!(Invoice.get(id).update(total += amount, rate *= factor))
+    );
+}
@@ -0,0 +1,65 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate chrono;
+extern crate postgres;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use tql::PrimaryKey;
+use tql_macros::to_sql;
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Session {
+    id: PrimaryKey,
+    length: chrono::Duration,
+    idle: Option<chrono::Duration>,
+}
+
+const SELECT: &str = "SELECT Session.id, Session.length, Session.idle";
+
+#[test]
+fn test_create() {
+    assert_eq!(
+        "CREATE TABLE Session (id SERIAL PRIMARY KEY NOT NULL, length INTERVAL NOT NULL, idle INTERVAL)",
+        to_sql!(Session.create())
+    );
+}
+
+#[test]
+fn test_select() {
+    assert_eq!(
+        format!("{} FROM Session WHERE Session.length > $1 AND idle IS NULL", SELECT),
+        to_sql!(Session.filter(length > max_length && idle.is_none()))
+    );
+}
+
+#[test]
+fn test_update() {
+    assert_eq!(
+        "UPDATE Session SET length = length + $1 WHERE Session.id = $2",
+        to_sql!(Session.get(id).update(length += extra))
+    );
+}
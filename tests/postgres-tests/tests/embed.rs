@@ -0,0 +1,77 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate postgres;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use tql::PrimaryKey;
+use tql_macros::to_sql;
+
+#[derive(Debug, SqlEmbed)]
+#[allow(dead_code)]
+struct Address {
+    street: String,
+    city: String,
+    zip: Option<i32>,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Customer {
+    id: PrimaryKey,
+    name: String,
+    #[tql(embed)]
+    address: Address,
+}
+
+const SELECT: &str = "SELECT Customer.id, Customer.name, Customer.address_street, Customer.address_city, Customer.address_zip";
+
+#[test]
+fn test_create() {
+    assert_eq!(
+        "CREATE TABLE Customer (id SERIAL PRIMARY KEY NOT NULL, name CHARACTER VARYING NOT NULL, address_street CHARACTER VARYING NOT NULL, address_city CHARACTER VARYING NOT NULL, address_zip INTEGER)",
+        to_sql!(Customer.create())
+    );
+}
+
+#[test]
+fn test_select() {
+    assert_eq!(
+        format!("{} FROM Customer WHERE Customer.address_city = $1", SELECT),
+        to_sql!(Customer.filter(address.city == city))
+    );
+}
+
+#[test]
+fn test_insert_update() {
+    assert_eq!(
+        "INSERT INTO Customer(name, address_street, address_city, address_zip) VALUES($1, $2, $3, $4) RETURNING id",
+        to_sql!(Customer.insert(name = name, address.street = street, address.city = city, address.zip = zip))
+    );
+    assert_eq!(
+        "UPDATE Customer SET address_city = $1 WHERE Customer.id = 1",
+        to_sql!(Customer.get(1).update(address.city = city))
+    );
+}
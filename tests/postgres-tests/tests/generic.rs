@@ -0,0 +1,99 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate postgres;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use std::borrow::Cow;
+
+use tql::{ForeignKey, PrimaryKey, SqlType};
+use tql_macros::to_sql;
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Note<'a> {
+    id: PrimaryKey,
+    text: Cow<'a, str>,
+    comment: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, SqlType)]
+#[tql(transparent)]
+struct Theme(String);
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Setting<T: SqlType<Repr = String>> {
+    id: PrimaryKey,
+    name: String,
+    value: T,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Profile<T> where T: SqlType<Repr = String> {
+    id: PrimaryKey,
+    setting: ForeignKey<Setting<T>>,
+}
+
+#[test]
+fn test_create() {
+    assert_eq!(
+        "CREATE TABLE Note (id SERIAL PRIMARY KEY NOT NULL, text CHARACTER VARYING NOT NULL, comment CHARACTER VARYING)",
+        to_sql!(Note.create())
+    );
+    assert_eq!(
+        "CREATE TABLE Setting (id SERIAL PRIMARY KEY NOT NULL, name CHARACTER VARYING NOT NULL, value CHARACTER VARYING NOT NULL)",
+        to_sql!(Setting.create())
+    );
+    assert_eq!(
+        "CREATE TABLE Profile (id SERIAL PRIMARY KEY NOT NULL, setting INTEGER REFERENCES Setting(id) NOT NULL)",
+        to_sql!(Profile.create())
+    );
+}
+
+#[test]
+fn test_select() {
+    assert_eq!(
+        "SELECT Note.id, Note.text, Note.comment FROM Note WHERE Note.text = $1",
+        to_sql!(Note.filter(text == text))
+    );
+    assert_eq!(
+        "SELECT Setting.id, Setting.name, Setting.value FROM Setting WHERE Setting.value = $1",
+        to_sql!(Setting::<Theme>.filter(value == value))
+    );
+}
+
+#[test]
+fn test_insert_delete() {
+    assert_eq!(
+        "INSERT INTO Setting(name, value) VALUES($1, $2) RETURNING id",
+        to_sql!(Setting::<Theme>.insert(name = name, value = value))
+    );
+    assert_eq!(
+        "DELETE FROM Setting WHERE Setting.name = $1",
+        to_sql!(Setting::<Theme>.filter(name == name).delete())
+    );
+}
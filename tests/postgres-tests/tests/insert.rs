@@ -46,6 +46,13 @@ struct RelatedTable {
     field1: String,
 }
 
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Country {
+    code: PrimaryKey<String>,
+    name: String,
+}
+
 #[test]
 fn test_insert() {
     assert_eq!(
@@ -65,3 +72,15 @@ fn test_insert() {
         to_sql!(Table.insert(field1 = "value1", field2 = 55, related_field = related_object, optional_field = 42))
     );
 }
+
+#[test]
+fn test_insert_natural_primary_key() {
+    assert_eq!(
+        "INSERT INTO Country(code, name) VALUES('CA', 'Canada') RETURNING code",
+        to_sql!(Country.insert(code = "CA", name = "Canada"))
+    );
+    assert_eq!(
+        "SELECT Country.code, Country.name FROM Country WHERE Country.code = 'CA'",
+        to_sql!(Country.get("CA"))
+    );
+}
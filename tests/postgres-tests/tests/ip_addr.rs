@@ -0,0 +1,66 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate postgres;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use std::net::IpAddr;
+
+use tql::PrimaryKey;
+use tql_macros::to_sql;
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Client {
+    id: PrimaryKey,
+    address: IpAddr,
+    proxy: Option<std::net::IpAddr>,
+}
+
+const SELECT: &str = "SELECT Client.id, Client.address, Client.proxy";
+
+#[test]
+fn test_create() {
+    assert_eq!(
+        "CREATE TABLE Client (id SERIAL PRIMARY KEY NOT NULL, address INET NOT NULL, proxy INET)",
+        to_sql!(Client.create())
+    );
+}
+
+#[test]
+fn test_select() {
+    assert_eq!(
+        format!("{} FROM Client WHERE Client.address = $1", SELECT),
+        to_sql!(Client.filter(address == address))
+    );
+    assert_eq!(
+        format!("{} FROM Client WHERE address <<= '10.0.0.0/8'::TEXT::INET", SELECT),
+        to_sql!(Client.filter(address.in_subnet("10.0.0.0/8")))
+    );
+    assert_eq!(
+        format!("{} FROM Client WHERE address <<= $1::TEXT::INET AND proxy IS NOT NULL", SELECT),
+        to_sql!(Client.filter(address.in_subnet(subnet) && proxy.is_some()))
+    );
+}
@@ -0,0 +1,115 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate postgres;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use tql::{ForeignKey, ManyToMany, PrimaryKey};
+use tql_macros::to_sql;
+
+#[derive(SqlTable)]
+#[tql(schema = "auth", table_name = "users")]
+#[allow(dead_code)]
+struct User {
+    id: PrimaryKey,
+    name: String,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Tag {
+    id: PrimaryKey,
+    name: String,
+}
+
+#[derive(SqlTable)]
+#[tql(schema = "billing")]
+#[allow(dead_code)]
+struct Invoice {
+    id: PrimaryKey,
+    total: i32,
+    customer: ForeignKey<User>,
+    tags: ManyToMany<Tag>,
+}
+
+#[test]
+fn test_create() {
+    assert_eq!(
+        "CREATE TABLE billing.Invoice (id SERIAL PRIMARY KEY NOT NULL, total INTEGER NOT NULL, customer INTEGER REFERENCES auth.users(id) NOT NULL)",
+        to_sql!(Invoice.create())
+    );
+    assert_eq!(
+        "CREATE TABLE billing.Invoice_tags (invoice INTEGER REFERENCES billing.Invoice(id) NOT NULL, tag INTEGER REFERENCES Tag(id) NOT NULL, PRIMARY KEY (invoice, tag))",
+        to_sql!(Invoice.tags.create())
+    );
+}
+
+#[test]
+fn test_drop() {
+    assert_eq!(
+        "DROP TABLE billing.Invoice",
+        to_sql!(Invoice.drop())
+    );
+}
+
+#[test]
+fn test_select() {
+    assert_eq!(
+        "SELECT billing.Invoice.id, billing.Invoice.total FROM billing.Invoice WHERE billing.Invoice.total > 10",
+        to_sql!(Invoice.filter(total > 10))
+    );
+    assert_eq!(
+        "SELECT billing.Invoice.id, billing.Invoice.total, customer_user.id, customer_user.name FROM billing.Invoice INNER JOIN auth.users AS customer_user ON billing.Invoice.customer = customer_user.id",
+        to_sql!(Invoice.join(customer))
+    );
+    assert_eq!(
+        "SELECT billing.Invoice.id, billing.Invoice.total FROM billing.Invoice WHERE billing.Invoice.id IN (SELECT billing.Invoice_tags.invoice FROM billing.Invoice_tags INNER JOIN Tag ON billing.Invoice_tags.tag = Tag.id WHERE Tag.name = $1)",
+        to_sql!(Invoice.filter(tags.name == name))
+    );
+}
+
+#[test]
+fn test_insert() {
+    assert_eq!(
+        "INSERT INTO billing.Invoice(total, customer) VALUES($1, $2) RETURNING id",
+        to_sql!(Invoice.insert(total = total, customer = customer))
+    );
+}
+
+#[test]
+fn test_update() {
+    assert_eq!(
+        "UPDATE billing.Invoice SET total = $1 WHERE billing.Invoice.id = $2",
+        to_sql!(Invoice.get(id).update(total = total))
+    );
+}
+
+#[test]
+fn test_delete() {
+    assert_eq!(
+        "DELETE FROM billing.Invoice WHERE billing.Invoice.total < 0",
+        to_sql!(Invoice.filter(total < 0).delete())
+    );
+}
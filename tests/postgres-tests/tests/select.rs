@@ -50,6 +50,30 @@ struct RelatedTable {
     field1: String,
 }
 
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Transfer {
+    id: PrimaryKey,
+    source: ForeignKey<RelatedTable>,
+    destination: ForeignKey<RelatedTable>,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Comment {
+    id: PrimaryKey,
+    text: String,
+    parent: ForeignKey<Comment>,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Message {
+    id: PrimaryKey,
+    text: String,
+    reply_to: Option<ForeignKey<Message>>,
+}
+
 const SELECT: &str = "SELECT Table.id, Table.field1, Table.field2, Table.field3, Table.date";
 
 #[test]
@@ -231,15 +255,39 @@ fn test_get() {
 #[test]
 fn test_join() {
     assert_eq!(
-        format!("{}, RelatedTable.id, RelatedTable.field1 FROM Table INNER JOIN RelatedTable ON Table.related_field = RelatedTable.id", SELECT),
+        format!("{}, related_field_related_table.id, related_field_related_table.field1 FROM Table INNER JOIN RelatedTable AS related_field_related_table ON Table.related_field = related_field_related_table.id", SELECT),
         to_sql!(Table.join(related_field))
     );
     assert_eq!(
-        format!("{}, RelatedTable.id, RelatedTable.field1 FROM Table INNER JOIN RelatedTable ON Table.related_field = RelatedTable.id", SELECT),
+        format!("{}, related_field_related_table.id, related_field_related_table.field1 FROM Table INNER JOIN RelatedTable AS related_field_related_table ON Table.related_field = related_field_related_table.id", SELECT),
         to_sql!(Table.all().join(related_field))
     );
 }
 
+#[test]
+fn test_join_same_table() {
+    assert_eq!(
+        "SELECT Transfer.id, source_related_table.id, source_related_table.field1, destination_related_table.id, destination_related_table.field1 FROM Transfer INNER JOIN RelatedTable AS source_related_table ON Transfer.source = source_related_table.id  INNER JOIN RelatedTable AS destination_related_table ON Transfer.destination = destination_related_table.id",
+        to_sql!(Transfer.join(source, destination))
+    );
+}
+
+#[test]
+fn test_join_self() {
+    assert_eq!(
+        "SELECT Comment.id, Comment.text, parent_comment.id, parent_comment.text FROM Comment INNER JOIN Comment AS parent_comment ON Comment.parent = parent_comment.id",
+        to_sql!(Comment.join(parent))
+    );
+}
+
+#[test]
+fn test_left_join() {
+    assert_eq!(
+        "SELECT Message.id, Message.text, reply_to_message.id, reply_to_message.text FROM Message LEFT JOIN Message AS reply_to_message ON Message.reply_to = reply_to_message.id",
+        to_sql!(Message.join(reply_to))
+    );
+}
+
 #[test]
 fn test_limit() {
     assert_eq!(
@@ -0,0 +1,96 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate chrono;
+extern crate postgres;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use chrono::{DateTime, Utc};
+use tql::PrimaryKey;
+use tql_macros::to_sql;
+
+#[derive(SqlTable)]
+#[tql(soft_delete)]
+#[allow(dead_code)]
+struct Document {
+    id: PrimaryKey,
+    title: String,
+    size: i32,
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+const SELECT: &str = "SELECT Document.id, Document.title, Document.size, Document.deleted_at FROM Document";
+
+#[test]
+fn test_select() {
+    assert_eq!(
+        format!("{} WHERE Document.deleted_at IS NULL", SELECT),
+        to_sql!(Document.all())
+    );
+    assert_eq!(
+        format!("{} WHERE Document.deleted_at IS NULL AND (Document.title = $1 OR Document.size > 10)", SELECT),
+        to_sql!(Document.filter(title == title || size > 10))
+    );
+    assert_eq!(
+        format!("{} WHERE Document.deleted_at IS NULL AND (Document.id = $1)", SELECT),
+        to_sql!(Document.get(id))
+    );
+    assert_eq!(
+        SELECT,
+        to_sql!(Document.with_deleted())
+    );
+    assert_eq!(
+        format!("{} WHERE Document.title = $1", SELECT),
+        to_sql!(Document.filter(title == title).with_deleted())
+    );
+    assert_eq!(
+        format!("{} WHERE Document.deleted_at IS NOT NULL AND (Document.size > 10) ORDER BY title", SELECT),
+        to_sql!(Document.only_deleted().filter(size > 10).sort(title))
+    );
+}
+
+#[test]
+fn test_aggregate() {
+    assert_eq!(
+        "SELECT CAST(AVG(size) AS DOUBLE PRECISION) FROM Document WHERE Document.deleted_at IS NULL",
+        to_sql!(Document.aggregate(avg(size)))
+    );
+    assert_eq!(
+        "SELECT CAST(AVG(size) AS DOUBLE PRECISION) FROM Document",
+        to_sql!(Document.aggregate(avg(size)).with_deleted())
+    );
+}
+
+#[test]
+fn test_delete() {
+    assert_eq!(
+        "UPDATE Document SET deleted_at = CURRENT_TIMESTAMP WHERE Document.deleted_at IS NULL AND (Document.id = $1)",
+        to_sql!(Document.get(id).delete())
+    );
+    assert_eq!(
+        "DELETE FROM Document WHERE Document.deleted_at < $1",
+        to_sql!(Document.filter(deleted_at < date).hard_delete())
+    );
+}
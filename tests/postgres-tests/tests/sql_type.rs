@@ -0,0 +1,165 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate postgres;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use std::error::Error;
+
+use tql::{ForeignKey, PrimaryKey, SqlType};
+use tql_macros::to_sql;
+
+#[derive(Clone, Debug, SqlType)]
+#[tql(transparent)]
+struct Email(String);
+
+#[derive(Clone, Debug, SqlType)]
+#[tql(transparent)]
+struct Name {
+    value: String,
+}
+
+#[derive(Clone, Debug, SqlType)]
+#[tql(repr = "i64")]
+struct Price {
+    euros: i64,
+    cents: i64,
+}
+
+impl SqlType for Price {
+    type Repr = i64;
+
+    fn to_repr(&self) -> i64 {
+        self.euros * 100 + self.cents
+    }
+
+    fn from_repr(repr: i64) -> Result<Self, Box<Error + Sync + Send>> {
+        Ok(Price {
+            euros: repr / 100,
+            cents: repr % 100,
+        })
+    }
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Customer {
+    id: PrimaryKey,
+    email: Email,
+    name: Name,
+    balance: Price,
+    backup_email: Option<Email>,
+    subscription: ForeignKey<Subscription>,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Subscription {
+    email: PrimaryKey<Email>,
+    months: i32,
+}
+
+#[derive(Clone, Copy, Debug, SqlEnum)]
+enum Status {
+    Open,
+    InProgress,
+    Closed,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Ticket {
+    id: PrimaryKey,
+    status: Status,
+    previous_status: Option<Status>,
+}
+
+const SELECT: &str = "SELECT Customer.id, Customer.email, Customer.name, Customer.balance, Customer.backup_email";
+
+#[test]
+fn test_create() {
+    assert_eq!(
+        "CREATE TABLE Customer (id SERIAL PRIMARY KEY NOT NULL, email CHARACTER VARYING NOT NULL, name CHARACTER VARYING NOT NULL, balance BIGINT NOT NULL, backup_email CHARACTER VARYING, subscription CHARACTER VARYING REFERENCES Subscription(email) NOT NULL)",
+        to_sql!(Customer.create())
+    );
+    assert_eq!(
+        "CREATE TABLE Subscription (email CHARACTER VARYING PRIMARY KEY NOT NULL, months INTEGER NOT NULL)",
+        to_sql!(Subscription.create())
+    );
+}
+
+#[test]
+fn test_select() {
+    assert_eq!(
+        format!("{} FROM Customer WHERE Customer.email = $1", SELECT),
+        to_sql!(Customer.filter(email == email))
+    );
+    assert_eq!(
+        format!("{} FROM Customer WHERE Customer.balance >= $1 AND backup_email IS NOT NULL", SELECT),
+        to_sql!(Customer.filter(balance >= price && backup_email.is_some()))
+    );
+    assert_eq!(
+        "SELECT Subscription.email, Subscription.months FROM Subscription WHERE Subscription.email = $1",
+        to_sql!(Subscription.get(email))
+    );
+}
+
+#[test]
+fn test_insert_update() {
+    assert_eq!(
+        "INSERT INTO Customer(email, name, balance, backup_email, subscription) VALUES($1, $2, $3, $4, $5) RETURNING id",
+        to_sql!(Customer.insert(email = email, name = name, balance = balance, backup_email = backup_email,
+                                subscription = subscription))
+    );
+    assert_eq!(
+        "UPDATE Customer SET balance = $1 WHERE Customer.id = 1",
+        to_sql!(Customer.get(1).update(balance = balance))
+    );
+}
+
+#[test]
+fn test_enum() {
+    assert_eq!(
+        "CREATE TABLE Ticket (id SERIAL PRIMARY KEY NOT NULL, status Status NOT NULL, previous_status Status)",
+        to_sql!(Ticket.create())
+    );
+    assert_eq!(
+        "SELECT Ticket.id, Ticket.status, Ticket.previous_status FROM Ticket WHERE Ticket.status = $1",
+        to_sql!(Ticket.filter(status == Status::Open))
+    );
+    assert_eq!(
+        "INSERT INTO Ticket(status, previous_status) VALUES($1, $2) RETURNING id",
+        to_sql!(Ticket.insert(status = Status::InProgress, previous_status = Some(Status::Open)))
+    );
+}
+
+#[test]
+fn test_enum_from_repr() {
+    assert_eq!("Closed", Status::from_repr("Closed".to_string()).unwrap().to_repr());
+    assert_eq!(
+        "unknown variant Pending of Status",
+        Status::from_repr("Pending".to_string()).unwrap_err().to_string()
+    );
+}
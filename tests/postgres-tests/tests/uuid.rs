@@ -0,0 +1,91 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate postgres;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+extern crate uuid;
+
+use tql::{ForeignKey, PrimaryKey};
+use tql_macros::to_sql;
+use uuid::Uuid;
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Account {
+    id: PrimaryKey<Uuid>,
+    name: String,
+    token: Option<uuid::Uuid>,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Session {
+    id: PrimaryKey,
+    account: ForeignKey<Account>,
+}
+
+#[test]
+fn test_create() {
+    assert_eq!(
+        "CREATE TABLE Account (id UUID PRIMARY KEY NOT NULL, name CHARACTER VARYING NOT NULL, token UUID)",
+        to_sql!(Account.create())
+    );
+    assert_eq!(
+        "CREATE TABLE Session (id SERIAL PRIMARY KEY NOT NULL, account UUID REFERENCES Account(id) NOT NULL)",
+        to_sql!(Session.create())
+    );
+}
+
+#[test]
+fn test_select() {
+    assert_eq!(
+        "SELECT Account.id, Account.name, Account.token FROM Account WHERE Account.id = $1",
+        to_sql!(Account.get(id))
+    );
+    assert_eq!(
+        "SELECT Account.id, Account.name, Account.token FROM Account WHERE Account.token = $1",
+        to_sql!(Account.filter(token == token))
+    );
+}
+
+#[test]
+fn test_insert() {
+    assert_eq!(
+        "INSERT INTO Account(name, id) VALUES($1, $2) RETURNING id",
+        to_sql!(Account.insert(name = name))
+    );
+    assert_eq!(
+        "INSERT INTO Account(name, token, id) VALUES('tql', $1, $2) RETURNING id",
+        to_sql!(Account.insert(name = "tql", token = token))
+    );
+    assert_eq!(
+        "INSERT INTO Account(id, name) VALUES($1, $2) RETURNING id",
+        to_sql!(Account.insert(id = id, name = name))
+    );
+    assert_eq!(
+        "INSERT INTO Session(account) VALUES($1) RETURNING id",
+        to_sql!(Session.insert(account = account))
+    );
+}
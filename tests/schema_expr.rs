@@ -0,0 +1,95 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![cfg(feature = "postgres")]
+#![feature(proc_macro_hygiene)]
+
+#[macro_use]
+extern crate lazy_static;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+#[macro_use]
+mod connection;
+mod teardown;
+
+backend_extern_crate!();
+
+use std::sync::Mutex;
+
+use tql::{ForeignKey, PrimaryKey};
+use tql_macros::sql;
+
+use connection::{Connection, get_connection};
+use teardown::TearDown;
+
+#[derive(SqlTable)]
+#[tql(schema = "tql_schema_expr", table_name = "customers")]
+struct CustomerSchemaExpr {
+    id: PrimaryKey,
+    name: String,
+}
+
+#[derive(SqlTable)]
+#[tql(schema = "tql_schema_expr")]
+struct TableSchemaExpr {
+    id: PrimaryKey,
+    total: i32,
+    customer: ForeignKey<CustomerSchemaExpr>,
+}
+
+lazy_static! {
+    static ref LOCK: Mutex<Connection> = Mutex::new(get_connection());
+}
+
+#[test]
+fn test_schema() {
+    let connection = LOCK.lock().unwrap();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableSchemaExpr.drop());
+        let _ = sql!(CustomerSchemaExpr.drop());
+        let _ = connection.execute("DROP SCHEMA tql_schema_expr", &[]);
+    });
+
+    connection.execute("CREATE SCHEMA tql_schema_expr", &[]).unwrap();
+    let _ = sql!(CustomerSchemaExpr.create());
+    let _ = sql!(TableSchemaExpr.create());
+
+    let id = sql!(CustomerSchemaExpr.insert(name = "customer")).unwrap();
+    let customer = sql!(CustomerSchemaExpr.get(id)).unwrap();
+
+    let id = sql!(TableSchemaExpr.insert(total = 10, customer = customer)).unwrap();
+
+    let num_updated = sql!(TableSchemaExpr.get(id).update(total = 42)).unwrap();
+    assert_eq!(1, num_updated);
+
+    let table = sql!(TableSchemaExpr.get(id).join(customer)).unwrap();
+    assert_eq!(42, table.total);
+    assert_eq!("customer", table.customer.unwrap().name);
+
+    let num_deleted = sql!(TableSchemaExpr.filter(total > 10).delete()).unwrap();
+    assert_eq!(1, num_deleted);
+
+    let tables = sql!(TableSchemaExpr.all()).unwrap();
+    assert_eq!(0, tables.len());
+}
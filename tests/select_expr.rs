@@ -462,6 +462,15 @@ fn test_select() {
     assert_eq!(id5, table5.id);
     assert_eq!(related_field2.id, table5.related_field.unwrap().id);
 
+    let mut tables = related_field.table_select_exprs(&connection).unwrap();
+    assert_eq!(2, tables.len());
+    let_vec!(table1, table2 = tables);
+    assert_eq!(id1, table1.id);
+    assert_eq!(id2, table2.id);
+
+    let tables = related_field2.table_select_exprs(&connection).unwrap();
+    assert_eq!(3, tables.len());
+
     let mut tables = sql!(TableSelectExpr.all()[..2]).unwrap();
     assert_eq!(2, tables.len());
     let_vec!(table1, table2 = tables);
@@ -0,0 +1,67 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate chrono;
+extern crate rusqlite;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use tql::PrimaryKey;
+use tql_macros::to_sql;
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Article {
+    id: PrimaryKey,
+    title: String,
+    #[tql(auto_now_add)]
+    created: DateTime<Utc>,
+    #[tql(auto_now)]
+    modified: NaiveDateTime,
+}
+
+#[test]
+fn test_insert() {
+    assert_eq!(
+        "INSERT INTO Article(title, created, modified) VALUES($1, strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'), strftime('%Y-%m-%dT%H:%M:%f', 'now'))",
+        to_sql!(Article.insert(title = title))
+    );
+    assert_eq!(
+        "INSERT INTO Article(title, created, modified) VALUES($1, $2, strftime('%Y-%m-%dT%H:%M:%f', 'now'))",
+        to_sql!(Article.insert(title = title, created = created))
+    );
+}
+
+#[test]
+fn test_update() {
+    assert_eq!(
+        "UPDATE Article SET title = $1, modified = strftime('%Y-%m-%dT%H:%M:%f', 'now') WHERE Article.id = 1",
+        to_sql!(Article.get(1).update(title = title))
+    );
+    assert_eq!(
+        "UPDATE Article SET title = $1, modified = $2 WHERE Article.id = $3",
+        to_sql!(Article.get(id).update(title = title, modified = modified))
+    );
+}
@@ -0,0 +1,89 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate rusqlite;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use tql::{ForeignKey, PrimaryKey};
+use tql_macros::to_sql;
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+#[tql(primary_key(order_id, line_no))]
+struct OrderLine {
+    order_id: i32,
+    line_no: i32,
+    quantity: i32,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Shipment {
+    id: PrimaryKey,
+    order_id: i32,
+    line_no: i32,
+    #[tql(foreign_key(order_id, line_no))]
+    line: ForeignKey<OrderLine>,
+}
+
+#[test]
+fn test_create() {
+    assert_eq!(
+        "CREATE TABLE OrderLine (order_id INTEGER NOT NULL, line_no INTEGER NOT NULL, quantity INTEGER NOT NULL, PRIMARY KEY (order_id, line_no))",
+        to_sql!(OrderLine.create())
+    );
+    assert_eq!(
+        "CREATE TABLE Shipment (id INTEGER PRIMARY KEY NOT NULL, order_id INTEGER NOT NULL, line_no INTEGER NOT NULL, FOREIGN KEY (order_id, line_no) REFERENCES OrderLine(order_id, line_no))",
+        to_sql!(Shipment.create())
+    );
+}
+
+#[test]
+fn test_get() {
+    assert_eq!(
+        "SELECT OrderLine.order_id, OrderLine.line_no, OrderLine.quantity FROM OrderLine WHERE OrderLine.order_id = 1 AND OrderLine.line_no = 2",
+        to_sql!(OrderLine.get((1, 2)))
+    );
+    assert_eq!(
+        "SELECT OrderLine.order_id, OrderLine.line_no, OrderLine.quantity FROM OrderLine WHERE OrderLine.order_id = $1 AND OrderLine.line_no = $2",
+        to_sql!(OrderLine.get((order_id, line_no)))
+    );
+    assert_eq!(
+        "UPDATE OrderLine SET quantity = 3 WHERE OrderLine.order_id = 1 AND OrderLine.line_no = 2",
+        to_sql!(OrderLine.get((1, 2)).update(quantity = 3))
+    );
+    assert_eq!(
+        "DELETE FROM OrderLine WHERE OrderLine.order_id = $1 AND OrderLine.line_no = 2",
+        to_sql!(OrderLine.get((order_id, 2)).delete())
+    );
+}
+
+#[test]
+fn test_join() {
+    assert_eq!(
+        "SELECT Shipment.id, Shipment.order_id, Shipment.line_no, line_order_line.order_id, line_order_line.line_no, line_order_line.quantity FROM Shipment INNER JOIN OrderLine AS line_order_line ON Shipment.order_id = line_order_line.order_id AND Shipment.line_no = line_order_line.line_no",
+        to_sql!(Shipment.join(line))
+    );
+}
@@ -75,6 +75,79 @@ struct OtherTypes {
     int64: i64,
 }
 
+#[derive(SqlTable)]
+#[allow(dead_code)]
+#[tql(unique_together(first_name, last_name))]
+struct Constraints {
+    id: PrimaryKey,
+    #[tql(unique)]
+    email: String,
+    #[tql(check = "LENGTH(first_name) > 0")]
+    first_name: String,
+    last_name: String,
+    #[tql(check = "age >= 0", unique)]
+    age: i32,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct BigTable {
+    id: PrimaryKey<i64>,
+    field1: String,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Country {
+    code: PrimaryKey<String>,
+    name: String,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct City {
+    id: PrimaryKey,
+    name: String,
+    country: ForeignKey<Country>,
+    big_table: ForeignKey<BigTable>,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Comment {
+    id: PrimaryKey,
+    text: String,
+    parent: ForeignKey<Comment>,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Message {
+    id: PrimaryKey,
+    text: String,
+    reply_to: Option<ForeignKey<Message>>,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Reply {
+    id: PrimaryKey,
+    #[tql(on_delete = "cascade")]
+    message: ForeignKey<Message>,
+    #[tql(on_delete = "set_null", on_update = "restrict")]
+    quote: Option<ForeignKey<Message>>,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct User {
+    id: PrimaryKey,
+    #[tql(max_length = 30)]
+    username: String,
+    #[tql(max_length = 100)]
+    bio: Option<String>,
+}
+
 #[test]
 fn test_create() {
     assert_eq!(
@@ -94,3 +167,59 @@ fn test_create() {
         to_sql!(OtherTypes.create())
     );
 }
+
+#[test]
+fn test_create_constraints() {
+    assert_eq!(
+        "CREATE TABLE Constraints (id INTEGER PRIMARY KEY NOT NULL, email CHARACTER VARYING NOT NULL UNIQUE, first_name CHARACTER VARYING NOT NULL CHECK (LENGTH(first_name) > 0), last_name CHARACTER VARYING NOT NULL, age INTEGER NOT NULL UNIQUE CHECK (age >= 0), UNIQUE (first_name, last_name))",
+        to_sql!(Constraints.create())
+    );
+}
+
+#[test]
+fn test_create_primary_key_types() {
+    assert_eq!(
+        "CREATE TABLE BigTable (id INTEGER PRIMARY KEY NOT NULL, field1 CHARACTER VARYING NOT NULL)",
+        to_sql!(BigTable.create())
+    );
+    assert_eq!(
+        "CREATE TABLE Country (code CHARACTER VARYING PRIMARY KEY NOT NULL, name CHARACTER VARYING NOT NULL)",
+        to_sql!(Country.create())
+    );
+    assert_eq!(
+        "CREATE TABLE City (id INTEGER PRIMARY KEY NOT NULL, name CHARACTER VARYING NOT NULL, country CHARACTER VARYING REFERENCES Country(code) NOT NULL, big_table BIGINT REFERENCES BigTable(id) NOT NULL)",
+        to_sql!(City.create())
+    );
+}
+
+#[test]
+fn test_create_self_reference() {
+    assert_eq!(
+        "CREATE TABLE Comment (id INTEGER PRIMARY KEY NOT NULL, text CHARACTER VARYING NOT NULL, parent INTEGER REFERENCES Comment(id) NOT NULL)",
+        to_sql!(Comment.create())
+    );
+}
+
+#[test]
+fn test_create_nullable_foreign_key() {
+    assert_eq!(
+        "CREATE TABLE Message (id INTEGER PRIMARY KEY NOT NULL, text CHARACTER VARYING NOT NULL, reply_to INTEGER REFERENCES Message(id))",
+        to_sql!(Message.create())
+    );
+}
+
+#[test]
+fn test_create_referential_actions() {
+    assert_eq!(
+        "CREATE TABLE Reply (id INTEGER PRIMARY KEY NOT NULL, message INTEGER REFERENCES Message(id) ON DELETE CASCADE NOT NULL, quote INTEGER REFERENCES Message(id) ON DELETE SET NULL ON UPDATE RESTRICT)",
+        to_sql!(Reply.create())
+    );
+}
+
+#[test]
+fn test_create_max_length() {
+    assert_eq!(
+        "CREATE TABLE User (id INTEGER PRIMARY KEY NOT NULL, username VARCHAR(30) NOT NULL, bio VARCHAR(100))",
+        to_sql!(User.create())
+    );
+}
@@ -0,0 +1,90 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate rusqlite;
+extern crate serde_json;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use tql::{Json, PrimaryKey, SqlType};
+use tql_macros::to_sql;
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Document {
+    id: PrimaryKey,
+    tags: Json<Vec<String>>,
+    data: serde_json::Value,
+    metadata: Option<serde_json::Value>,
+}
+
+// NOTE: only serde_json::Value is stored as JSON, not another type with the same name.
+#[derive(Clone, Debug, SqlType)]
+#[tql(transparent)]
+struct Value(String);
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Setting {
+    id: PrimaryKey,
+    value: Value,
+}
+
+const SELECT: &str = "SELECT Document.id, Document.tags, Document.data, Document.metadata";
+
+#[test]
+fn test_create() {
+    assert_eq!(
+        "CREATE TABLE Document (id INTEGER PRIMARY KEY NOT NULL, tags TEXT NOT NULL, data TEXT NOT NULL, metadata TEXT)",
+        to_sql!(Document.create())
+    );
+    assert_eq!(
+        "CREATE TABLE Setting (id INTEGER PRIMARY KEY NOT NULL, value CHARACTER VARYING NOT NULL)",
+        to_sql!(Setting.create())
+    );
+}
+
+#[test]
+fn test_select() {
+    assert_eq!(
+        format!("{} FROM Document WHERE JSON_EXTRACT(data, '$.\"' || 'name' || '\"') = 'tql'", SELECT),
+        to_sql!(Document.filter(data.get("name") == "tql"))
+    );
+    assert_eq!(
+        format!("{} FROM Document WHERE JSON_TYPE(data, '$.\"' || 'name' || '\"') IS NOT NULL", SELECT),
+        to_sql!(Document.filter(data.has_key("name")))
+    );
+    assert_eq!(
+        format!("{} FROM Document WHERE JSON_TYPE(metadata, '$.\"' || 'version' || '\"') IS NOT NULL", SELECT),
+        to_sql!(Document.filter(metadata.has_key("version")))
+    );
+}
+
+#[test]
+fn test_insert() {
+    assert_eq!(
+        "INSERT INTO Document(tags, data, metadata) VALUES($1, $2, $3)",
+        to_sql!(Document.insert(tags = tags, data = data, metadata = None))
+    );
+}
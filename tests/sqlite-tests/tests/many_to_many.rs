@@ -0,0 +1,137 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate rusqlite;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use tql::{ForeignKey, ManyToMany, PrimaryKey};
+use tql_macros::to_sql;
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Tag {
+    id: PrimaryKey,
+    name: String,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Article {
+    id: PrimaryKey,
+    title: String,
+    tags: ManyToMany<Tag>,
+    #[tql(through = "TagAssignment")]
+    labels: ManyToMany<Tag>,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct TagAssignment {
+    id: PrimaryKey,
+    article: ForeignKey<Article>,
+    tag: ForeignKey<Tag>,
+    weight: i32,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Person {
+    id: PrimaryKey,
+    friends: ManyToMany<Person>,
+}
+
+#[test]
+fn test_create() {
+    assert_eq!(
+        "CREATE TABLE Article (id INTEGER PRIMARY KEY NOT NULL, title CHARACTER VARYING NOT NULL)",
+        to_sql!(Article.create())
+    );
+    assert_eq!(
+        "CREATE TABLE Article_tags (article INTEGER REFERENCES Article(id) NOT NULL, tag INTEGER REFERENCES Tag(id) NOT NULL, PRIMARY KEY (article, tag))",
+        to_sql!(Article.tags.create())
+    );
+    assert_eq!(
+        "CREATE TABLE Person_friends (from_person INTEGER REFERENCES Person(id) NOT NULL, to_person INTEGER REFERENCES Person(id) NOT NULL, PRIMARY KEY (from_person, to_person))",
+        to_sql!(Person.friends.create())
+    );
+    assert_eq!(
+        "DROP TABLE Article_tags",
+        to_sql!(Article.tags.drop())
+    );
+}
+
+#[test]
+fn test_add_remove() {
+    let id = 1;
+    let tag_id = 2;
+    assert_eq!(
+        "INSERT INTO Article_tags (article, tag) SELECT Article.id, tags_tag.id FROM Article, Tag AS tags_tag WHERE Article.id IN (SELECT Article.id FROM Article WHERE Article.id = $1) AND tags_tag.id = $2",
+        to_sql!(Article.get(id).tags.add(tag_id))
+    );
+    assert_eq!(
+        "INSERT INTO Article_tags (article, tag) SELECT Article.id, tags_tag.id FROM Article, Tag AS tags_tag WHERE Article.id IN (SELECT Article.id FROM Article) AND tags_tag.id = 2",
+        to_sql!(Article.tags.add(2))
+    );
+    assert_eq!(
+        "DELETE FROM Article_tags WHERE article IN (SELECT Article.id FROM Article WHERE Article.title = 'Rust') AND tag = $1",
+        to_sql!(Article.filter(title == "Rust").tags.remove(tag_id))
+    );
+    assert_eq!(
+        "DELETE FROM Article_tags WHERE article IN (SELECT Article.id FROM Article WHERE Article.id = 1)",
+        to_sql!(Article.get(1).tags.clear())
+    );
+    assert_eq!(
+        "INSERT INTO Person_friends (from_person, to_person) SELECT Person.id, friends_person.id FROM Person, Person AS friends_person WHERE Person.id IN (SELECT Person.id FROM Person WHERE Person.id = 1) AND friends_person.id = 2",
+        to_sql!(Person.get(1).friends.add(2))
+    );
+    assert_eq!(
+        "DELETE FROM Person_friends WHERE from_person IN (SELECT Person.id FROM Person WHERE Person.id = 1) AND to_person = 2",
+        to_sql!(Person.get(1).friends.remove(2))
+    );
+}
+
+#[test]
+fn test_filter() {
+    assert_eq!(
+        "SELECT Article.id, Article.title FROM Article WHERE Article.id IN (SELECT Article_tags.article FROM Article_tags INNER JOIN Tag ON Article_tags.tag = Tag.id WHERE Tag.name = 'Rust')",
+        to_sql!(Article.filter(tags.name == "Rust"))
+    );
+    assert_eq!(
+        "SELECT Article.id, Article.title FROM Article WHERE Article.id IN (SELECT TagAssignment.article FROM TagAssignment INNER JOIN Tag ON TagAssignment.tag = Tag.id WHERE Tag.name = 'Rust') AND Article.title = 'Release'",
+        to_sql!(Article.filter(labels.name == "Rust" && title == "Release"))
+    );
+}
+
+#[test]
+fn test_join() {
+    assert_eq!(
+        "SELECT Article.id, Article.title, tags_tag.id, tags_tag.name FROM Article LEFT JOIN Article_tags ON Article.id = Article_tags.article LEFT JOIN Tag AS tags_tag ON Article_tags.tag = tags_tag.id",
+        to_sql!(Article.join(tags))
+    );
+    assert_eq!(
+        "SELECT Article.id, Article.title, labels_tag.id, labels_tag.name FROM Article LEFT JOIN TagAssignment ON Article.id = TagAssignment.article LEFT JOIN Tag AS labels_tag ON TagAssignment.tag = labels_tag.id",
+        to_sql!(Article.join(labels))
+    );
+}
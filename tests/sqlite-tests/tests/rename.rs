@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate rusqlite;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use tql::{ForeignKey, PrimaryKey};
+use tql_macros::to_sql;
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+#[tql(table_name = "people")]
+struct Person {
+    #[tql(column = "person_id")]
+    id: PrimaryKey,
+    #[tql(column = "full_name")]
+    name: String,
+    #[tql(column = "user_age")]
+    age: i32,
+    #[tql(column = "address_id")]
+    address: ForeignKey<Address>,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+#[tql(table_name = "addresses")]
+struct Address {
+    id: PrimaryKey,
+    #[tql(column = "city_name")]
+    city: String,
+}
+
+const SELECT: &str = "SELECT people.person_id, people.full_name, people.user_age";
+
+#[test]
+fn test_create() {
+    assert_eq!(
+        "CREATE TABLE people (person_id INTEGER PRIMARY KEY NOT NULL, full_name CHARACTER VARYING NOT NULL, user_age INTEGER NOT NULL, address_id INTEGER REFERENCES addresses(id) NOT NULL)",
+        to_sql!(Person.create())
+    );
+    assert_eq!(
+        "DROP TABLE people",
+        to_sql!(Person.drop())
+    );
+}
+
+#[test]
+fn test_select() {
+    assert_eq!(
+        format!("{} FROM people", SELECT),
+        to_sql!(Person.all())
+    );
+    assert_eq!(
+        format!("{} FROM people WHERE people.full_name = 'John' AND people.user_age >= 18", SELECT),
+        to_sql!(Person.filter(name == "John" && age >= 18))
+    );
+    assert_eq!(
+        format!("{} FROM people WHERE people.person_id = 1", SELECT),
+        to_sql!(Person.get(1))
+    );
+    assert_eq!(
+        format!("{} FROM people WHERE LENGTH(full_name) = 4", SELECT),
+        to_sql!(Person.filter(name.len() == 4))
+    );
+    assert_eq!(
+        format!("{} FROM people ORDER BY user_age DESC, full_name", SELECT),
+        to_sql!(Person.sort(-age, name))
+    );
+}
+
+#[test]
+fn test_join() {
+    assert_eq!(
+        format!("{}, address_address.id, address_address.city_name FROM people INNER JOIN addresses AS address_address ON people.address_id = address_address.id", SELECT),
+        to_sql!(Person.join(address))
+    );
+}
+
+#[test]
+fn test_insert_update_delete() {
+    assert_eq!(
+        "INSERT INTO people(full_name, user_age, address_id) VALUES('John', 42, $1)",
+        to_sql!(Person.insert(name = "John", age = 42, address = address))
+    );
+    assert_eq!(
+        "UPDATE people SET full_name = 'Jane', user_age = user_age + 1 WHERE people.person_id = 1",
+        to_sql!(Person.get(1).update(name = "Jane", age += 1))
+    );
+    assert_eq!(
+        "DELETE FROM people WHERE people.user_age < 18",
+        to_sql!(Person.filter(age < 18).delete())
+    );
+}
+
+#[test]
+fn test_aggregate() {
+    assert_eq!(
+        "SELECT AVG(user_age) FROM people GROUP BY full_name",
+        to_sql!(Person.values(name).aggregate(avg(age)))
+    );
+}
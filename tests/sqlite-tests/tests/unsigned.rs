@@ -0,0 +1,87 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+extern crate rusqlite;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use tql::{PrimaryKey, Unsigned};
+use tql_macros::to_sql;
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct Counter {
+    id: PrimaryKey,
+    port: u16,
+    hits: u32,
+    total: Option<u64>,
+}
+
+const SELECT: &str = "SELECT Counter.id, Counter.port, Counter.hits, Counter.total";
+
+#[test]
+fn test_create() {
+    assert_eq!(
+        "CREATE TABLE Counter (id INTEGER PRIMARY KEY NOT NULL, port INTEGER NOT NULL, hits BIGINT NOT NULL, total BIGINT)",
+        to_sql!(Counter.create())
+    );
+}
+
+#[test]
+fn test_select() {
+    assert_eq!(
+        format!("{} FROM Counter WHERE Counter.port = 8080 AND Counter.hits > 10", SELECT),
+        to_sql!(Counter.filter(port == 8080 && hits > 10u32))
+    );
+    assert_eq!(
+        format!("{} FROM Counter WHERE Counter.total < $1", SELECT),
+        to_sql!(Counter.filter(total < limit))
+    );
+}
+
+#[test]
+fn test_update() {
+    assert_eq!(
+        "UPDATE Counter SET hits = hits + $1 WHERE Counter.id = $2",
+        to_sql!(Counter.get(id).update(hits += count))
+    );
+}
+
+#[test]
+fn test_range() {
+    assert_eq!(65_535, u16::max_value().to_signed().unwrap());
+    assert_eq!(4_294_967_295, u32::max_value().to_signed().unwrap());
+    assert!(u64::max_value().to_signed().is_err());
+    assert_eq!(Some(42), Some(42u64).to_signed().unwrap());
+
+    assert_eq!(65_535, u16::from_signed(65_535).unwrap());
+    assert!(u16::from_signed(65_536).is_err());
+    assert!(u32::from_signed(-1).is_err());
+    assert!(u32::from_signed(4_294_967_296).is_err());
+    assert_eq!(None, Option::<u64>::from_signed(None).unwrap());
+    assert_eq!(
+        "the value -1 is out of range for the type u64",
+        u64::from_signed(-1).unwrap_err().to_string()
+    );
+}
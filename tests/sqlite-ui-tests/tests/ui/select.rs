@@ -28,6 +28,8 @@ extern crate tql;
 #[macro_use]
 extern crate tql_macros;
 
+use std::net::IpAddr;
+
 use rusqlite::Connection;
 use tql::PrimaryKey;
 use tql_macros::sql;
@@ -41,6 +43,7 @@ struct Table {
     id: PrimaryKey,
     field1: String,
     i32_field: i32,
+    address: IpAddr,
 }
 
 fn main() {
@@ -48,4 +51,5 @@ fn main() {
 
     sql!(Table.filter(field1.regex(r"%3")));
     sql!(Table.filter(field1.regex(r"%E3")));
+    sql!(Table.filter(address.in_subnet("10.0.0.0/8")));
 }
@@ -0,0 +1,56 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Tests of the max_length attribute.
+
+#![feature(proc_macro_hygiene)]
+
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+#[macro_use]
+mod connection;
+backend_extern_crate!();
+
+use connection::{Connection, get_connection};
+use tql::PrimaryKey;
+use tql_macros::sql;
+
+#[derive(SqlTable)]
+struct Table {
+    id: PrimaryKey,
+    #[tql(max_length = 5)]
+    field1: String,
+    field2: String,
+}
+
+fn main() {
+    let connection = get_connection();
+
+    sql!(Table.insert(field1 = "abcdef", field2 = ""));
+    //~^ ERROR the string literal assigned to the field `field1` is longer than its max_length (6 > 5)
+
+    sql!(Table.insert(field1 = "ééééé", field2 = "abcdef"));
+
+    sql!(Table.get(1).update(field1 = "abcdef"));
+    //~^ ERROR the string literal assigned to the field `field1` is longer than its max_length (6 > 5)
+}
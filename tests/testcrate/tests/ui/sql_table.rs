@@ -31,7 +31,9 @@ extern crate tql_macros;
 mod connection; 
 backend_extern_crate!();
 
-use tql::{DateTime,ForeignKey};
+use std::borrow::Cow;
+
+use tql::{DateTime, ForeignKey, PrimaryKey, SqlType};
 
 struct Connection {
     value: String,
@@ -42,9 +44,9 @@ struct Table<'a> {
     string: &'a str,
     //~^ ERROR use of unsupported type name `& 'a str`
     connection: Connection,
-    //~^ ERROR use of unsupported type name `Connection`
+    //~^ ERROR the trait bound `Connection: tql::SqlType` is not satisfied
     connection2: Option<Connection>,
-    //~^ ERROR use of unsupported type name `Connection`
+    //~^ ERROR the trait bound `Connection: tql::SqlType` is not satisfied
     nested_options: Option<Option<String>>,
     //~^ ERROR use of unsupported type name `Option<String>`
     datetime: DateTime,
@@ -57,8 +59,14 @@ struct Table<'a> {
     //~^ ERROR use of unsupported type name `Option`
     vector: Vec,
     //~^ ERROR use of unsupported type name `Vec`
-    vector_i32: Vec<i32>,
-    //~^ ERROR use of unsupported type name `Vec<i32>`
+}
+
+// NOTE: the lifetime and type parameters are passed through to the generated code.
+#[derive(SqlTable)]
+struct Borrowed<'a, T: SqlType<Repr = String>> {
+    id: PrimaryKey,
+    text: Cow<'a, str>,
+    value: T,
 }
 
 fn main() {
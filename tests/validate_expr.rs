@@ -0,0 +1,149 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro_hygiene)]
+
+#[macro_use]
+extern crate lazy_static;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+#[macro_use]
+mod connection;
+mod teardown;
+
+backend_extern_crate!();
+
+use std::cell::Cell;
+use std::sync::Mutex;
+
+use tql::{PrimaryKey, ValidationError};
+use tql_macros::sql;
+
+use connection::{Connection, get_connection};
+use teardown::TearDown;
+
+mod validators {
+    pub fn positive(value: &i32) -> Result<(), &'static str> {
+        if *value > 0 {
+            Ok(())
+        }
+        else {
+            Err("must be positive")
+        }
+    }
+}
+
+fn not_empty(value: &str) -> Result<(), String> {
+    if value.is_empty() {
+        return Err("cannot be empty".to_string());
+    }
+    Ok(())
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct TableValidateExpr {
+    id: PrimaryKey,
+    #[tql(validate = "not_empty")]
+    field1: String,
+    #[tql(validate = "validators::positive")]
+    field2: i32,
+}
+
+lazy_static! {
+    static ref LOCK: Mutex<Connection> = Mutex::new(get_connection());
+}
+
+fn next_value(calls: &Cell<i32>) -> String {
+    calls.set(calls.get() + 1);
+    format!("value{}", calls.get())
+}
+
+#[test]
+fn test_insert() {
+    let connection = LOCK.lock().unwrap();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableValidateExpr.drop());
+    });
+
+    let _ = sql!(TableValidateExpr.create());
+
+    let error = sql!(TableValidateExpr.insert(field1 = "", field2 = 1)).unwrap_err();
+    let validation_error = ValidationError::from_error(&error).expect("validation error");
+    assert_eq!("field1", validation_error.field());
+    assert_eq!("cannot be empty", validation_error.message());
+
+    let field2 = 0;
+    let error = sql!(TableValidateExpr.insert(field1 = "value", field2 = field2)).unwrap_err();
+    let validation_error = ValidationError::from_error(&error).expect("validation error");
+    assert_eq!("field2", validation_error.field());
+    assert_eq!("must be positive", validation_error.message());
+
+    let calls = Cell::new(0);
+    let id = sql!(TableValidateExpr.insert(field1 = next_value(&calls), field2 = 1)).unwrap();
+    assert_eq!(1, calls.get());
+
+    let table = sql!(TableValidateExpr.get(id)).unwrap();
+    assert_eq!("value1", table.field1);
+
+    let tables = sql!(TableValidateExpr.all()).unwrap();
+    assert_eq!(1, tables.len());
+}
+
+#[test]
+fn test_update() {
+    let connection = LOCK.lock().unwrap();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableValidateExpr.drop());
+    });
+
+    let _ = sql!(TableValidateExpr.create());
+
+    let id = sql!(TableValidateExpr.insert(field1 = "value", field2 = 1)).unwrap();
+
+    let field1 = String::new();
+    let error = sql!(TableValidateExpr.get(id).update(field1 = field1, field2 = 2)).unwrap_err();
+    assert_eq!("field1", ValidationError::from_error(&error).expect("validation error").field());
+
+    let table = sql!(TableValidateExpr.get(id)).unwrap();
+    assert_eq!("value", table.field1);
+    assert_eq!(1, table.field2);
+
+    let num_updated = sql!(TableValidateExpr.get(id).update(field2 += -5)).unwrap();
+    assert_eq!(1, num_updated);
+
+    let table = sql!(TableValidateExpr.get(id)).unwrap();
+    assert_eq!(-4, table.field2);
+}
+
+#[test]
+fn test_validation_error() {
+    let error = ValidationError::new("field1", "cannot be empty");
+    assert_eq!("invalid value for the field field1: cannot be empty", error.to_string());
+
+    let result: connection::Result<()> = Err(error.into());
+    let error = result.unwrap_err();
+    assert_eq!("field1", ValidationError::from_error(&error).expect("validation error").field());
+}
@@ -25,6 +25,8 @@ use proc_macro2::Span;
 use syn::{
     BinOp,
     Expr,
+    ExprField,
+    Member,
 };
 use syn::spanned::Spanned;
 
@@ -42,7 +44,14 @@ use super::path_expr_to_identifier;
 pub fn argument_to_assignment(arg: &Expression) -> Result<Assignment> {
     fn assign_values(assignment: &mut Assignment, expr1: &Expression, expr2: &Expression, errors: &mut Vec<Error>) {
         assignment.value = expr2.clone();
-        if let Some(identifier) = path_expr_to_identifier(expr1, errors) {
+        // A field of an embedded struct.
+        if let Expr::Field(ExprField { ref base, member: Member::Named(ref field), .. }) = *expr1 {
+            if let Some(identifier) = path_expr_to_identifier(base, errors) {
+                assignment.identifier = Some(identifier);
+                assignment.embedded_field = Some(field.clone());
+            }
+        }
+        else if let Some(identifier) = path_expr_to_identifier(expr1, errors) {
             assignment.identifier = Some(identifier);
         }
     }
@@ -50,6 +59,7 @@ pub fn argument_to_assignment(arg: &Expression) -> Result<Assignment> {
     let mut errors = vec![];
     let mut assignment = Assignment {
         identifier: None,
+        embedded_field: None,
         operator: WithSpan {
             node: AssignmentOperator::Equal,
             span: arg.span(),
@@ -25,8 +25,10 @@ use proc_macro2::Span;
 use syn::{
     BinOp,
     Expr,
+    ExprField,
     ExprUnary,
     Ident,
+    Member,
     Path,
     UnOp,
 };
@@ -159,6 +161,23 @@ pub fn expression_to_filter_expression(arg: &Expression, table_name: &str) -> Re
                     span: arg.span(),
                 })
             },
+            // A field of the items related by a ManyToMany field.
+            Expr::Field(ExprField { ref base, member: Member::Named(ref field), .. }) => {
+                if let Expr::Path(ref path) = **base {
+                    let relation = path.path.segments.first().unwrap().into_value().ident.clone();
+                    FilterExpression::FilterValue(WithSpan {
+                        node: FilterValue::RelatedField(table_name.to_string(), relation, field.clone()),
+                        span: arg.span(),
+                    })
+                }
+                else {
+                    errors.push(Error::new(
+                        "Expected many-to-many field", // TODO: improve this message.
+                        base.span(),
+                    ));
+                    FilterExpression::NoFilters
+                }
+            },
             Expr::Paren(ref paren) => {
                 let filter = expression_to_filter_expression(&paren.expr, table_name)?;
                 FilterExpression::ParenFilter(Box::new(filter))
@@ -232,8 +251,8 @@ fn path_method_call_to_filter(path: &Path, identifier: Ident, args: &Punctuated<
 
 pub fn get_method_calls(query: &Query) -> Vec<(ast::MethodCall, Option<Expression>)> {
     match *query {
-        Query::Aggregate { ref filter, .. } | Query::Delete { ref filter, .. } | Query::Select { ref filter, .. } |
-            Query::Update { ref filter, .. } =>
+        Query::Aggregate { ref filter, .. } | Query::Delete { ref filter, .. } |
+            Query::Relation { ref filter, .. } | Query::Select { ref filter, .. } | Query::Update { ref filter, .. } =>
             get_methods_from_filter(filter),
         Query::CreateTable { .. } | Query::Drop { .. } | Query::Insert { .. } =>
             vec![],
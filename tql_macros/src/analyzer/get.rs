@@ -21,14 +21,16 @@
 
 /// Analyzer for the get() method.
 
-use syn::Expr;
+use syn::{Expr, ExprTuple};
 
 use ast::{
     Expression,
     Filter,
     FilterExpression,
     FilterValue,
+    Filters,
     Limit,
+    LogicalOperator,
     RelationalOperator,
 };
 use error::{Result, res};
@@ -42,12 +44,29 @@ pub fn get_expression_to_filter_expression(arg: &Expression, table_name: &str) -
     match *arg {
         Expr::Lit(_) | Expr::Path(_) => {
             let filter = FilterExpression::Filter(Filter {
-                operand1: FilterValue::PrimaryKey(table_name.to_string()),
+                operand1: FilterValue::PrimaryKey(table_name.to_string(), None),
                 operator: RelationalOperator::Equal,
                 operand2: arg.clone(),
             });
             res((filter, true, Limit::NoLimit), vec![])
         },
+        // A tuple is used for a primary key with multiple columns.
+        Expr::Tuple(ExprTuple { ref elems, .. }) if !elems.is_empty() => {
+            let mut filters = elems.iter()
+                .enumerate()
+                .map(|(index, elem)| FilterExpression::Filter(Filter {
+                    operand1: FilterValue::PrimaryKey(table_name.to_string(), Some(index)),
+                    operator: RelationalOperator::Equal,
+                    operand2: elem.clone(),
+                }));
+            let first_filter = filters.next().expect("first primary key filter");
+            let filter = filters.fold(first_filter, |filter1, filter2| FilterExpression::Filters(Filters {
+                operand1: Box::new(filter1),
+                operator: LogicalOperator::And,
+                operand2: Box::new(filter2),
+            }));
+            res((filter, true, Limit::NoLimit), vec![])
+        },
         _ => expression_to_filter_expression(arg, table_name)
             .and_then(|filter| Ok((filter, false, Limit::Index(number_literal(0))))),
     }
@@ -30,12 +30,22 @@ pub fn analyze_methods(query: &Query) -> Result<()> {
     let mut errors = vec![];
     for (call, _) in calls {
         let name = call.method_name.to_string();
-        if let Some(method) = methods.get(&name) {
-            if method.template.is_none() {
-                errors.push(Error::new(&format!("The method {} is not available on this backend", &name),
+        if let Some(methods) = methods.get(&name) {
+            if methods.iter().all(|method| method.template.is_none()) {
+                errors.push(Error::new(&format!("The method {} is not available with {}", &name, backend_name()),
                     call.method_name.span()))
             }
         }
     }
     res((), errors)
 }
+
+/// Get the name of the database of the enabled backend.
+fn backend_name() -> &'static str {
+    if cfg!(feature = "postgres") {
+        "PostgreSQL"
+    }
+    else {
+        "SQLite"
+    }
+}
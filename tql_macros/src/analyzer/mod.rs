@@ -50,6 +50,7 @@ use ast::{
     Aggregate,
     AggregateFilterExpression,
     Assignment,
+    Deleted,
     Expression,
     FilterExpression,
     Groups,
@@ -57,6 +58,7 @@ use ast::{
     Limit,
     Order,
     Query,
+    RelationAction,
 };
 use error::{Error, Result, res};
 use parser::{MethodCall, MethodCalls};
@@ -88,6 +90,7 @@ enum SqlQueryType {
     Delete,
     Drop,
     Insert,
+    Relation,
     Select,
     SelectOne,
     Update,
@@ -112,9 +115,14 @@ struct QueryData {
     // Aggregate, Delete, Select, Update
     filter: FilterExpression,
     // Aggregate / Select
+    deleted: Deleted,
     joins: Vec<Join>,
+    // Delete
+    hard_delete: bool,
     // Insert / Update
     assignments: Vec<Assignment>,
+    // Relation
+    relation_action: Option<RelationAction>,
     // Select
     limit: Limit,
     order: Vec<Order>,
@@ -131,6 +139,7 @@ pub fn analyze(method_calls: &MethodCalls) -> Result<Query> {
 
     check_methods(&method_calls, &mut errors);
     check_method_calls_validity(&method_calls, &mut errors);
+    check_relation_calls(method_calls, &mut errors);
 
     let calls = &method_calls.calls;
     let mut delete_position = None;
@@ -138,7 +147,8 @@ pub fn analyze(method_calls: &MethodCalls) -> Result<Query> {
     // Get all the data from the query.
     let query_data = process_methods(&calls, &table_name, &mut delete_position)?;
 
-    let query = new_query(query_data, table_name);
+    let relation = method_calls.relation.as_ref().map(|(field, _)| field.clone());
+    let query = new_query(query_data, table_name, relation);
 
     check_delete_without_filters(&query, delete_position, &mut errors);
 
@@ -158,6 +168,9 @@ pub fn analyze_types(query: &Query) -> Result<()> {
         },
         Query::Drop { .. } => (), // Nothing to analyze.
         Query::Insert { .. } => (),
+        Query::Relation { ref filter, ref table, .. } => {
+            analyze_filter_types(filter, table, &mut errors);
+        },
         Query::Select { ref filter, ref limit, ref table, .. } => {
             analyze_filter_types(filter, &table, &mut errors);
             analyze_limit_types(limit, &mut errors);
@@ -203,12 +216,16 @@ fn check_delete_without_filters(query: &Query, delete_position: Option<Span>, er
 fn check_method_calls_validity(method_calls: &MethodCalls, errors: &mut Vec<Error>) {
     let method_map =
         hashmap!{
-            "aggregate" => vec!["filter", "join", "values"],
-            "all" => vec!["filter", "get", "join", "limit", "sort"],
+            "add" => vec!["filter", "get"],
+            "aggregate" => vec!["filter", "join", "only_deleted", "values", "with_deleted"],
+            "all" => vec!["filter", "get", "join", "limit", "only_deleted", "sort", "with_deleted"],
+            "clear" => vec!["filter", "get"],
             "create" => vec![],
             "delete" => vec!["filter", "get"],
             "drop" => vec![],
+            "hard_delete" => vec!["filter", "get"],
             "insert" => vec![],
+            "remove" => vec!["filter", "get"],
             "update" => vec!["filter", "get"],
         };
 
@@ -236,6 +253,41 @@ fn check_method_calls_validity(method_calls: &MethodCalls, errors: &mut Vec<Erro
     }
 }
 
+/// Check that the methods operating on the table of a `ManyToMany` field are called on this field
+/// and that no other methods are called on it.
+fn check_relation_calls(method_calls: &MethodCalls, errors: &mut Vec<Error>) {
+    let relation_methods = ["add", "clear", "create", "drop", "remove"];
+    let relation_index = method_calls.relation.as_ref()
+        .map(|&(_, index)| index)
+        .unwrap_or_else(|| method_calls.calls.len());
+    for (index, call) in method_calls.calls.iter().enumerate() {
+        let name = call.name.to_string();
+        if index >= relation_index && !relation_methods.contains(&name.as_str()) {
+            errors.push(Error::new(
+                &format!("cannot call the {}() method on a many-to-many field", name),
+                call.name.span(),
+            ));
+        }
+        else if index < relation_index && ["add", "clear", "remove"].contains(&name.as_str()) {
+            errors.push(Error::new(
+                &format!("the {}() method can only be called on a many-to-many field", name),
+                call.name.span(),
+            ));
+        }
+    }
+
+    if let Some((ref field, index)) = method_calls.relation {
+        if index == method_calls.calls.len() {
+            let mut error = Error::new(
+                &format!("expected a method call on the many-to-many field `{}`", field),
+                field.span(),
+            );
+            error.add_help("call one of add(), clear(), create(), drop() or remove()");
+            errors.push(error);
+        }
+    }
+}
+
 /// Check if the method `calls` exist.
 fn check_methods(method_calls: &MethodCalls, errors: &mut Vec<Error>) {
     let methods = get_methods();
@@ -278,6 +330,24 @@ fn check_no_arguments(method_call: &MethodCall, errors: &mut Vec<Error>) {
     }
 }
 
+/// Check that the specified method call received a single argument.
+fn check_one_argument(method_call: &MethodCall, errors: &mut Vec<Error>) -> bool {
+    let length = method_call.args.len();
+    if length == 1 {
+        true
+    }
+    else {
+        errors.push(Error::new_with_code(
+            &format!("this method takes 1 parameter but {param_count} parameter{plural} supplied",
+                    param_count = length,
+                    plural = plural_verb(length)
+                   ),
+            method_call.name.span(), "E0061"
+        ));
+        false
+    }
+}
+
 /// Check if the `field_type` is compatible with the `expression`'s type.
 pub fn check_type(field_type: &Type, expression: &Expression, errors: &mut Vec<Error>) {
     if field_type != expression {
@@ -306,19 +376,25 @@ fn convert_arguments<F, Type>(arguments: &[Expression], convert_argument: F) ->
 // TODO: return Vec<&'static str> instead?
 fn get_methods() -> Vec<String> {
     vec![
+        "add".to_string(),
         "aggregate".to_string(),
         "all".to_string(),
+        "clear".to_string(),
         "create".to_string(),
         "delete".to_string(),
         "drop".to_string(),
         "filter".to_string(),
         "get".to_string(),
+        "hard_delete".to_string(),
         "insert".to_string(),
         "join".to_string(),
         "limit".to_string(),
+        "only_deleted".to_string(),
+        "remove".to_string(),
         "sort".to_string(),
         "update".to_string(),
         "values".to_string(),
+        "with_deleted".to_string(),
     ]
 }
 
@@ -378,14 +454,34 @@ fn mismatched_types<S: Display, T: Display>(expected_type: S, actual_type: &T, p
 }
 
 /// Create a new query from all the data gathered by the method calls.
-fn new_query(QueryData { filter, joins, limit, order, assignments, aggregates, groups,
-    aggregate_filter, query_type, use_pk }: QueryData, table_name: String) -> Query
+/// The `relation` is the `ManyToMany` field on which the methods are called, if any.
+fn new_query(QueryData { filter, deleted, joins, hard_delete, limit, order, assignments, aggregates, groups,
+    aggregate_filter, query_type, relation_action, use_pk }: QueryData, table_name: String,
+    relation: Option<Ident>) -> Query
 {
+    if let Some(field) = relation {
+        let action =
+            match query_type {
+                SqlQueryType::CreateTable => RelationAction::Create,
+                SqlQueryType::Drop => RelationAction::Drop,
+                // NOTE: when there is no action, an error was reported by check_relation_calls()
+                // or check_one_argument(), so it does not matter which one is used.
+                _ => relation_action.unwrap_or(RelationAction::Clear),
+            };
+        return Query::Relation {
+            action,
+            field,
+            filter,
+            table: table_name,
+        };
+    }
+
     match query_type {
         SqlQueryType::Aggregate =>
             Query::Aggregate {
                 aggregates,
                 aggregate_filter,
+                deleted,
                 filter,
                 groups,
                 joins,
@@ -398,6 +494,7 @@ fn new_query(QueryData { filter, joins, limit, order, assignments, aggregates, g
         SqlQueryType::Delete =>
             Query::Delete {
                 filter,
+                hard: hard_delete,
                 table: table_name,
                 use_pk,
             },
@@ -410,8 +507,15 @@ fn new_query(QueryData { filter, joins, limit, order, assignments, aggregates, g
                 assignments,
                 table: table_name,
             },
+        // NOTE: without a ManyToMany field, an error was reported by check_relation_calls(), so it
+        // does not matter which query is created.
+        SqlQueryType::Relation =>
+            Query::Drop {
+                table: table_name,
+            },
         SqlQueryType::Select | SqlQueryType::SelectOne => {
             Query::Select {
+                deleted,
                 filter,
                 get: query_type == SqlQueryType::SelectOne,
                 joins,
@@ -462,6 +566,12 @@ fn process_methods(calls: &[MethodCall], table_name: &str, delete_position: &mut
 
     for method_call in calls {
         match method_call.name.to_string().as_str() {
+            "add" => {
+                if check_one_argument(method_call, &mut errors) {
+                    query_data.relation_action = Some(RelationAction::Add(method_call.args[0].clone()));
+                }
+                query_data.query_type = SqlQueryType::Relation;
+            },
             "aggregate" => {
                 try(convert_arguments(&method_call.args, argument_to_aggregate), &mut errors, |aggrs| {
                     query_data.aggregates = aggrs;
@@ -471,6 +581,11 @@ fn process_methods(calls: &[MethodCall], table_name: &str, delete_position: &mut
             "all" => {
                 check_no_arguments(&method_call, &mut errors);
             },
+            "clear" => {
+                check_no_arguments(method_call, &mut errors);
+                query_data.relation_action = Some(RelationAction::Clear);
+                query_data.query_type = SqlQueryType::Relation;
+            },
             "create" => {
                 check_no_arguments(&method_call, &mut errors);
                 query_data.query_type = SqlQueryType::CreateTable;
@@ -514,6 +629,12 @@ fn process_methods(calls: &[MethodCall], table_name: &str, delete_position: &mut
                 }
                 query_data.query_type = SqlQueryType::SelectOne;
             },
+            "hard_delete" => {
+                check_no_arguments(method_call, &mut errors);
+                query_data.hard_delete = true;
+                query_data.query_type = SqlQueryType::Delete;
+                *delete_position = Some(method_call.name.span());
+            },
             "insert" => {
                 try(convert_arguments(&method_call.args, argument_to_assignment), &mut errors, |assigns| {
                     query_data.assignments = assigns;
@@ -537,6 +658,16 @@ fn process_methods(calls: &[MethodCall], table_name: &str, delete_position: &mut
                     query_data.limit = new_limit;
                 });
             },
+            "only_deleted" => {
+                check_no_arguments(method_call, &mut errors);
+                query_data.deleted = Deleted::Only;
+            },
+            "remove" => {
+                if check_one_argument(method_call, &mut errors) {
+                    query_data.relation_action = Some(RelationAction::Remove(method_call.args[0].clone()));
+                }
+                query_data.query_type = SqlQueryType::Relation;
+            },
             "sort" => {
                 try(convert_arguments(&method_call.args, argument_to_order), &mut errors, |new_order| {
                     query_data.order = new_order;
@@ -553,6 +684,10 @@ fn process_methods(calls: &[MethodCall], table_name: &str, delete_position: &mut
                     query_data.groups = new_groups;
                 });
             },
+            "with_deleted" => {
+                check_no_arguments(method_call, &mut errors);
+                query_data.deleted = Deleted::Include;
+            },
             _ => (), // NOTE: Nothing to do since check_methods() check for unknown method.
         }
     }
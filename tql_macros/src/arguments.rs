@@ -31,20 +31,27 @@ use ast::{
     Aggregate,
     AggregateFilterExpression,
     Assignment,
+    AssignmentOperator,
     Expression,
     FilterExpression,
     FilterValue,
     Limit,
     MethodCall,
     Query,
+    RelationAction,
 };
 
 /// A Rust expression to be send as a parameter to the SQL query function.
 #[derive(Clone, Debug)]
 pub struct Arg {
+    /// Whether the argument is assigned to the field with `=` in `insert()` or `update()`.
+    pub assigned: bool,
     pub expression: Expression,
     pub field_name: Option<Ident>,
     pub field_name_prefix: Option<String>,
+    /// The `ManyToMany` field through which the field is accessed, or the embedded field containing it.
+    /// Without `field_name`, the argument is the primary key of an item of this field.
+    pub relation: Option<Ident>,
 }
 
 /// A collection of `Arg`s.
@@ -55,9 +62,11 @@ fn add(arguments: &mut Args, literals: &mut Args, field_name: Option<Ident>, fie
        expr: Expression)
 {
     add_expr(arguments, literals, Arg {
+        assigned: false,
         expression: expr,
         field_name_prefix,
         field_name,
+        relation: None,
     });
 }
 
@@ -66,7 +75,24 @@ fn add_assignments(assignments: Vec<Assignment>, arguments: &mut Args, literals:
     for assign in assignments {
         let field_name = assign.identifier.expect("Assignment identifier");
         // NOTE: At this stage (code generation), the field exists, hence unwrap().
-        add(arguments, literals, Some(field_name), None, assign.value);
+        match assign.embedded_field {
+            Some(embedded_field) =>
+                add_expr(arguments, literals, Arg {
+                    assigned: false,
+                    expression: assign.value,
+                    field_name: Some(embedded_field),
+                    field_name_prefix: None,
+                    relation: Some(field_name),
+                }),
+            None =>
+                add_expr(arguments, literals, Arg {
+                    assigned: assign.operator.node == AssignmentOperator::Equal,
+                    expression: assign.value,
+                    field_name: Some(field_name),
+                    field_name_prefix: None,
+                    relation: None,
+                }),
+        }
     }
 }
 
@@ -138,9 +164,11 @@ fn add_limit_arguments(limit: Limit, arguments: &mut Args, literals: &mut Args)
             let expression = parse((quote! { #expression2 - #offset }).into())
                 .expect("Subtraction quoted expression");
             add_expr(arguments, literals, Arg {
+                assigned: false,
                 expression,
                 field_name: None,
                 field_name_prefix: None,
+                relation: None,
             });
             add(arguments, literals, None, None, expression1);
         },
@@ -152,9 +180,11 @@ fn add_limit_arguments(limit: Limit, arguments: &mut Args, literals: &mut Args)
 fn add_with_method(args: &mut Args, literals: &mut Args, expr: Expression)
 {
     add_expr(args, literals, Arg {
+        assigned: false,
         expression: expr,
         field_name: None,
         field_name_prefix: None,
+        relation: None,
     });
 }
 
@@ -183,11 +213,22 @@ fn add_filter_value_arguments(filter_value: &FilterValue, args: &mut Args, liter
             }
         },
         FilterValue::None => unreachable!("FilterValue::None in add_filter_value_arguments()"),
-        FilterValue::PrimaryKey(ref table) => {
+        FilterValue::PrimaryKey(ref table, _) => {
             if let Some(expr) = expression {
                 add(args, literals, None, Some(table.clone()), expr);
             }
         },
+        FilterValue::RelatedField(_, ref relation, ref identifier) => {
+            if let Some(expr) = expression {
+                add_expr(args, literals, Arg {
+                    assigned: false,
+                    expression: expr,
+                    field_name: Some(identifier.clone()),
+                    field_name_prefix: None,
+                    relation: Some(relation.clone()),
+                });
+            }
+        },
     }
 }
 
@@ -209,6 +250,21 @@ pub fn arguments(query: Query) -> (Args, Args) {
         Query::Insert { assignments, .. } => {
             add_assignments(assignments, &mut arguments, &mut literals);
         },
+        Query::Relation { action, field, filter, .. } => {
+            add_filter_arguments(filter, &mut arguments, &mut literals);
+            match action {
+                RelationAction::Add(expr) | RelationAction::Remove(expr) =>
+                    // NOTE: the argument is the primary key of the related item.
+                    add_expr(&mut arguments, &mut literals, Arg {
+                        assigned: false,
+                        expression: expr,
+                        field_name: None,
+                        field_name_prefix: None,
+                        relation: Some(field),
+                    }),
+                RelationAction::Clear | RelationAction::Create | RelationAction::Drop => (),
+            }
+        },
         Query::Select { filter, limit, ..} => {
             add_filter_arguments(filter, &mut arguments, &mut literals);
             add_limit_arguments(limit, &mut arguments, &mut literals);
@@ -83,6 +83,8 @@ pub struct AggregateFilters {
 #[derive(Debug)]
 pub struct Assignment {
     pub identifier: Option<Ident>,
+    /// The field assigned in the struct of the embedded field `identifier`, like `city` in `address.city = …`.
+    pub embedded_field: Option<Ident>,
     pub operator: WithSpan<AssignmentOperator>,
     pub value: Expression,
 }
@@ -125,6 +127,18 @@ pub struct Filter {
     pub operand2: Expression,
 }
 
+/// The rows of a table with `#[tql(soft_delete)]` selected by a query.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Deleted {
+    /// Exclude the deleted rows, which is the default.
+    #[default]
+    Exclude,
+    /// Comes from `with_deleted()`.
+    Include,
+    /// Comes from `only_deleted()`.
+    Only,
+}
+
 /// Either a single `Filter`, `Filters`, `NegFilter`, `NoFilters`, `ParenFilter` or a `FilterValue`.
 #[derive(Debug)]
 pub enum FilterExpression {
@@ -159,7 +173,12 @@ pub enum FilterValue {
     None,
     Identifier(String, Ident),
     MethodCall(MethodCall),
-    PrimaryKey(String),
+    /// The primary key of the table, or one of its columns when the primary key has multiple
+    /// columns.
+    PrimaryKey(String, Option<usize>),
+    /// A field of the items related by a `ManyToMany` field or of an embedded struct (the second
+    /// `Ident`).
+    RelatedField(String, Ident, Ident),
 }
 
 /// A `Join` with another table via a specific `joined_field`.
@@ -219,6 +238,19 @@ pub enum Order {
     NoOrder,
 }
 
+/// An operation on the table of a `ManyToMany` field.
+#[derive(Debug)]
+pub enum RelationAction {
+    /// Add the related item given as argument to the selected items.
+    Add(Expression),
+    /// Remove all the related items of the selected items.
+    Clear,
+    Create,
+    Drop,
+    /// Remove the related item given as argument from the selected items.
+    Remove(Expression),
+}
+
 /// `RelationalOperator` to be used in a `Filter`.
 #[derive(Clone, Copy, Debug)]
 pub enum RelationalOperator {
@@ -236,6 +268,7 @@ pub enum Query {
     Aggregate {
         aggregates: Vec<Aggregate>,
         aggregate_filter: AggregateFilterExpression,
+        deleted: Deleted,
         filter: FilterExpression,
         groups: Groups,
         joins: Vec<Join>,
@@ -246,6 +279,8 @@ pub enum Query {
     },
     Delete {
         filter: FilterExpression,
+        /// Whether the rows of a table with `#[tql(soft_delete)]` are deleted with `hard_delete()`.
+        hard: bool,
         table: String,
         use_pk: bool,
     },
@@ -256,7 +291,14 @@ pub enum Query {
         assignments: Vec<Assignment>,
         table: String,
     },
+    Relation {
+        action: RelationAction,
+        field: Ident,
+        filter: FilterExpression,
+        table: String,
+    },
     Select {
+        deleted: Deleted,
         filter: FilterExpression,
         get: bool,
         joins: Vec<Join>,
@@ -284,13 +326,6 @@ pub enum QueryType {
     SelectOne,
 }
 
-/// An SQL field with its type.
-#[derive(Debug)]
-pub struct TypedField {
-    pub identifier: String,
-    pub typ: TokenStream,
-}
-
 /// Get the query type.
 pub fn query_type(query: &Query) -> QueryType {
     match *query {
@@ -303,6 +338,8 @@ pub fn query_type(query: &Query) -> QueryType {
             }
         },
         Query::Insert { .. } => QueryType::InsertOne,
+        Query::Relation { action: RelationAction::Create, .. } => QueryType::Create,
+        Query::Relation { .. } => QueryType::Exec,
         Query::Select { get, ref limit, .. } => {
             let mut typ = QueryType::SelectMulti;
             if get {
@@ -27,16 +27,111 @@ use std::fmt::Write;
 use syn::{
     self,
     AngleBracketedGenericArguments,
+    Attribute,
     Field,
+    Ident,
+    ItemStruct,
+    Lit,
+    Meta,
+    MetaList,
+    MetaNameValue,
+    NestedMeta,
     PathArguments,
     TypePath,
 };
+use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
+use syn::token::Comma;
 
 use ast::WithSpan;
+use error::{Error, Result, res};
 use state::{BothTypes, SqlFields};
+use string::{pluralize, to_snake_case};
 use types::Type;
 
+/// The `#[tql(…)]` attributes of a model struct.
+#[derive(Debug, Default)]
+pub struct TableAttributes {
+    /// The fields from `#[tql(primary_key(…))]`, for a primary key with multiple columns.
+    pub primary_key: Vec<Ident>,
+    /// The schema from `#[tql(schema = "…")]` containing the table.
+    pub schema: Option<String>,
+    /// Whether `#[tql(soft_delete)]` is used to mark the deleted rows in the `deleted_at` column
+    /// instead of deleting them.
+    pub soft_delete: bool,
+    /// The name from `#[tql(table_name = "…")]` of the table, instead of the name of the struct.
+    pub table_name: Option<String>,
+    /// The groups of fields from `#[tql(unique_together(…))]`.
+    pub unique_together: Vec<Vec<Ident>>,
+}
+
+/// The `#[tql(…)]` attributes of a model field.
+#[derive(Debug, Default)]
+pub struct FieldAttributes {
+    /// Whether `#[tql(auto_now)]` is used to set the field to the current time by every insert and update.
+    pub auto_now: bool,
+    /// Whether `#[tql(auto_now_add)]` is used to set the field to the current time by the inserts.
+    pub auto_now_add: bool,
+    /// The SQL condition from `#[tql(check = "…")]` added as a `CHECK` constraint of the column.
+    pub check: Option<String>,
+    /// The name from `#[tql(column = "…")]` of the column, instead of the name of the field.
+    pub column: Option<String>,
+    /// Whether `#[tql(embed)]` is used to store the fields of a struct with `#[derive(SqlEmbed)]` in
+    /// columns prefixed by the column of this field.
+    pub embed: bool,
+    /// The fields from `#[tql(foreign_key(…))]` which hold the value of a `ForeignKey` to a table
+    /// with a primary key with multiple columns.
+    pub foreign_key: Vec<Ident>,
+    /// The maximum number of characters from `#[tql(max_length = …)]` of a `String` field.
+    pub max_length: Option<u64>,
+    /// The action from `#[tql(on_delete = "…")]` when the related row is deleted.
+    pub on_delete: Option<ReferentialAction>,
+    /// The action from `#[tql(on_update = "…")]` when the primary key of the related row is updated.
+    pub on_update: Option<ReferentialAction>,
+    /// The total number of digits from `#[tql(precision = …)]` of a `Decimal` field.
+    pub precision: Option<u64>,
+    /// The name from `#[tql(related_name = "…")]` of the method getting the rows referencing an
+    /// item of the related table.
+    pub related_name: Option<Ident>,
+    /// The number of digits after the decimal point from `#[tql(scale = …)]` of a `Decimal` field.
+    pub scale: Option<u64>,
+    /// The model from `#[tql(through = "…")]` used as the link table of a `ManyToMany` field.
+    pub through: Option<Ident>,
+    /// Whether `#[tql(unique)]` is used to add a `UNIQUE` constraint to the column.
+    pub unique: bool,
+    /// The function from `#[tql(validate = "…")]` checking the value assigned to the field by the
+    /// inserts and updates.
+    pub validate: Option<syn::Path>,
+}
+
+/// The `#[tql(…)]` attributes of a struct with `#[derive(SqlType)]`.
+#[derive(Debug, Default)]
+pub struct SqlTypeAttributes {
+    /// The type from `#[tql(repr = "…")]` used to store the values when the `SqlType` trait is
+    /// implemented manually.
+    pub repr: Option<syn::Type>,
+    /// Whether `#[tql(transparent)]` is used to store a single-field struct as its field.
+    pub transparent: bool,
+}
+
+/// The action done on a row when the row it references with a `ForeignKey` is deleted or updated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReferentialAction {
+    Cascade,
+    Restrict,
+    SetNull,
+}
+
+impl ReferentialAction {
+    pub fn to_sql(self) -> &'static str {
+        match self {
+            ReferentialAction::Cascade => "CASCADE",
+            ReferentialAction::Restrict => "RESTRICT",
+            ReferentialAction::SetNull => "SET NULL",
+        }
+    }
+}
+
 /// Convert a type from the Rust AST to the SQL `Type`.
 //#[allow(cmp_owned)]
 pub fn field_ty_to_type(ty: &syn::Type) -> WithSpan<Type> {
@@ -85,3 +180,230 @@ pub fn fields_vec_to_hashmap(fields: &[Field]) -> SqlFields {
     }
     sql_fields
 }
+
+/// Get the `#[tql(…)]` attributes of a model struct.
+pub fn table_attributes(attrs: &[Attribute]) -> Result<TableAttributes> {
+    let mut errors = vec![];
+    let mut attributes = TableAttributes::default();
+    for meta in tql_metas(attrs, &mut errors) {
+        match meta {
+            Meta::NameValue(MetaNameValue { ref ident, ref lit, .. }) if ident == "table_name" =>
+                attributes.table_name = string_value(lit, &mut errors),
+            Meta::List(MetaList { ref ident, ref nested, .. }) if ident == "primary_key" =>
+                attributes.primary_key = field_idents(nested, &mut errors),
+            Meta::NameValue(MetaNameValue { ref ident, ref lit, .. }) if ident == "schema" =>
+                attributes.schema = string_value(lit, &mut errors),
+            Meta::Word(ref ident) if ident == "soft_delete" => attributes.soft_delete = true,
+            Meta::List(MetaList { ref ident, ref nested, .. }) if ident == "unique_together" =>
+                attributes.unique_together.push(field_idents(nested, &mut errors)),
+            _ => errors.push(unknown_attribute(&meta)),
+        }
+    }
+    res(attributes, errors)
+}
+
+/// Get the `#[tql(…)]` attributes of a model field.
+pub fn field_attributes(attrs: &[Attribute]) -> Result<FieldAttributes> {
+    let mut errors = vec![];
+    let mut attributes = FieldAttributes::default();
+    for meta in tql_metas(attrs, &mut errors) {
+        match meta {
+            Meta::Word(ref ident) if ident == "auto_now" => attributes.auto_now = true,
+            Meta::Word(ref ident) if ident == "auto_now_add" => attributes.auto_now_add = true,
+            Meta::NameValue(MetaNameValue { ref ident, ref lit, .. }) if ident == "check" =>
+                attributes.check = string_value(lit, &mut errors),
+            Meta::NameValue(MetaNameValue { ref ident, ref lit, .. }) if ident == "column" =>
+                attributes.column = string_value(lit, &mut errors),
+            Meta::Word(ref ident) if ident == "embed" => attributes.embed = true,
+            Meta::List(MetaList { ref ident, ref nested, .. }) if ident == "foreign_key" =>
+                attributes.foreign_key = field_idents(nested, &mut errors),
+            Meta::NameValue(MetaNameValue { ref ident, ref lit, .. }) if ident == "max_length" =>
+                attributes.max_length = int_value(lit, &mut errors),
+            Meta::NameValue(MetaNameValue { ref ident, ref lit, .. }) if ident == "on_delete" =>
+                attributes.on_delete = referential_action_value(lit, &mut errors),
+            Meta::NameValue(MetaNameValue { ref ident, ref lit, .. }) if ident == "on_update" =>
+                attributes.on_update = referential_action_value(lit, &mut errors),
+            Meta::NameValue(MetaNameValue { ref ident, ref lit, .. }) if ident == "precision" =>
+                attributes.precision = int_value(lit, &mut errors),
+            Meta::NameValue(MetaNameValue { ref ident, ref lit, .. }) if ident == "scale" =>
+                attributes.scale = int_value(lit, &mut errors),
+            Meta::NameValue(MetaNameValue { ref ident, ref lit, .. }) if ident == "through" =>
+                attributes.through = ident_value(lit, "struct name", &mut errors),
+            Meta::NameValue(MetaNameValue { ref ident, ref lit, .. }) if ident == "related_name" =>
+                attributes.related_name = ident_value(lit, "method name", &mut errors),
+            Meta::Word(ref ident) if ident == "unique" => attributes.unique = true,
+            Meta::NameValue(MetaNameValue { ref ident, ref lit, .. }) if ident == "validate" =>
+                attributes.validate = path_value(lit, &mut errors),
+            _ => errors.push(unknown_attribute(&meta)),
+        }
+    }
+    res(attributes, errors)
+}
+
+/// Get the `#[tql(…)]` attributes of a struct with `#[derive(SqlType)]`.
+pub fn sql_type_attributes(attrs: &[Attribute]) -> Result<SqlTypeAttributes> {
+    let mut errors = vec![];
+    let mut attributes = SqlTypeAttributes::default();
+    for meta in tql_metas(attrs, &mut errors) {
+        match meta {
+            Meta::NameValue(MetaNameValue { ref ident, ref lit, .. }) if ident == "repr" =>
+                attributes.repr = type_value(lit, &mut errors),
+            Meta::Word(ref ident) if ident == "transparent" => attributes.transparent = true,
+            _ => errors.push(unknown_attribute(&meta)),
+        }
+    }
+    res(attributes, errors)
+}
+
+/// Get the name of the SQL table for the model struct, qualified by the schema from `#[tql(schema = "…")]`.
+/// It is either the name specified in `#[tql(table_name = "…")]` or the struct name converted with the
+/// naming convention selected by the features.
+pub fn table_name(item_struct: &ItemStruct) -> String {
+    let attributes = table_attributes(&item_struct.attrs).unwrap_or_default();
+    let name = unqualified_table_name(item_struct, attributes.table_name);
+    match attributes.schema {
+        Some(schema) => format!("{}.{}", schema, name),
+        None => name,
+    }
+}
+
+fn unqualified_table_name(item_struct: &ItemStruct, table_name: Option<String>) -> String {
+    if let Some(table_name) = table_name {
+        return table_name;
+    }
+    let name = item_struct.ident.to_string();
+    let name =
+        if cfg!(feature = "snake_case_tables") {
+            to_snake_case(&name)
+        }
+        else {
+            name
+        };
+    if cfg!(feature = "plural_tables") {
+        pluralize(&name)
+    }
+    else {
+        name
+    }
+}
+
+/// Get the name of the SQL column for the model field.
+/// It is either the name specified in `#[tql(column = "…")]` or the field name.
+pub fn column_name(field: &Field) -> String {
+    if let Ok(FieldAttributes { column: Some(column), .. }) = field_attributes(&field.attrs) {
+        return column;
+    }
+    field.ident.as_ref().expect("field has name").to_string()
+}
+
+/// Get the items inside all the `#[tql(…)]` attributes.
+fn tql_metas(attrs: &[Attribute], errors: &mut Vec<Error>) -> Vec<Meta> {
+    let mut metas = vec![];
+    for attr in attrs {
+        if attr.path.segments.len() != 1 || attr.path.segments[0].ident != "tql" {
+            continue;
+        }
+        match attr.parse_meta() {
+            Ok(Meta::List(list)) => {
+                for nested in list.nested {
+                    match nested {
+                        NestedMeta::Meta(meta) => metas.push(meta),
+                        NestedMeta::Literal(literal) =>
+                            errors.push(Error::new("expected attribute name, found literal", literal.span())),
+                    }
+                }
+            },
+            _ => errors.push(Error::new("expected #[tql(…)]", attr.span())),
+        }
+    }
+    metas
+}
+
+/// Get the field names in an attribute like `primary_key(field1, field2)`.
+fn field_idents(nested: &Punctuated<NestedMeta, Comma>, errors: &mut Vec<Error>) -> Vec<Ident> {
+    let mut idents = vec![];
+    for meta in nested {
+        if let NestedMeta::Meta(Meta::Word(ref ident)) = *meta {
+            idents.push(ident.clone());
+        }
+        else {
+            errors.push(Error::new("expected field name", meta.span()));
+        }
+    }
+    idents
+}
+
+/// Get the identifier in a string literal in an attribute.
+fn ident_value(lit: &Lit, expected: &str, errors: &mut Vec<Error>) -> Option<Ident> {
+    let value = string_value(lit, errors)?;
+    match syn::parse_str(&value) {
+        Ok(ident) => Some(ident),
+        Err(_) => {
+            errors.push(Error::new(&format!("expected {}", expected), lit.span()));
+            None
+        },
+    }
+}
+
+/// Get the value of an integer literal in an attribute.
+fn int_value(lit: &Lit, errors: &mut Vec<Error>) -> Option<u64> {
+    if let Lit::Int(ref int) = *lit {
+        Some(int.value())
+    }
+    else {
+        errors.push(Error::new("expected integer literal", lit.span()));
+        None
+    }
+}
+
+/// Get the type in a string literal in an attribute.
+fn type_value(lit: &Lit, errors: &mut Vec<Error>) -> Option<syn::Type> {
+    let value = string_value(lit, errors)?;
+    match syn::parse_str(&value) {
+        Ok(typ) => Some(typ),
+        Err(_) => {
+            errors.push(Error::new("expected type", lit.span()));
+            None
+        },
+    }
+}
+
+/// Get the path in a string literal in an attribute.
+fn path_value(lit: &Lit, errors: &mut Vec<Error>) -> Option<syn::Path> {
+    let value = string_value(lit, errors)?;
+    match syn::parse_str(&value) {
+        Ok(path) => Some(path),
+        Err(_) => {
+            errors.push(Error::new("expected function path", lit.span()));
+            None
+        },
+    }
+}
+
+/// Get the referential action in a string literal in an attribute.
+fn referential_action_value(lit: &Lit, errors: &mut Vec<Error>) -> Option<ReferentialAction> {
+    match string_value(lit, errors)?.as_str() {
+        "cascade" => Some(ReferentialAction::Cascade),
+        "restrict" => Some(ReferentialAction::Restrict),
+        "set_null" => Some(ReferentialAction::SetNull),
+        _ => {
+            errors.push(Error::new("expected one of `cascade`, `restrict` or `set_null`", lit.span()));
+            None
+        },
+    }
+}
+
+/// Get the value of a string literal in an attribute.
+fn string_value(lit: &Lit, errors: &mut Vec<Error>) -> Option<String> {
+    if let Lit::Str(ref string) = *lit {
+        Some(string.value())
+    }
+    else {
+        errors.push(Error::new("expected string literal", lit.span()));
+        None
+    }
+}
+
+fn unknown_attribute(meta: &Meta) -> Error {
+    Error::new(&format!("unknown tql attribute `{}`", meta.name()), meta.span())
+}
@@ -20,7 +20,7 @@
  */
 
 use proc_macro2::TokenStream;
-use syn::{Expr, Ident};
+use syn::{Expr, Generics, Ident, Type};
 
 use super::BackendGen;
 use SqlQueryWithArgs;
@@ -33,6 +33,10 @@ pub fn create_backend() -> DummyBackend {
 }
 
 impl BackendGen for DummyBackend {
+    fn connection_type(&self) -> TokenStream {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
     fn convert_index(&self, _index: usize) -> TokenStream {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
@@ -51,15 +55,36 @@ impl BackendGen for DummyBackend {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
 
+    fn result_type(&self, _typ: TokenStream) -> TokenStream {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
     fn row_type_ident(&self, _table_ident: &Ident) -> TokenStream {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
 
+    fn select_multi(&self, _connection_expr: TokenStream, _sql_query: &TokenStream, _args_expr: TokenStream,
+                    _struct_expr: TokenStream) -> TokenStream
+    {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
+    fn sql_enum_conversions(&self, _type_ident: &Ident, _variants: &[Ident]) -> TokenStream {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
+    fn sql_type_conversions(&self, _type_ident: &Ident, _repr: &Type) -> TokenStream {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
     fn to_sql(&self, _primary_key_ident: &Ident) -> TokenStream {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
 
-    fn to_sql_impl(&self, _table_ident: &Ident, _to_sql_code: TokenStream) -> TokenStream {
+    fn to_sql_impl(&self, _table_ident: &Ident, _generics: &Generics, _primary_key_type: Option<&Type>,
+                   _to_sql_code: TokenStream)
+        -> TokenStream
+    {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
 }
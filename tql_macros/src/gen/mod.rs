@@ -27,33 +27,53 @@ mod postgres;
 mod sqlite;
 
 use proc_macro::TokenStream;
-use proc_macro2::Span;
+use proc_macro2::{Literal, Span, TokenTree};
 use proc_macro2::TokenStream as Tokens;
+use quote::ToTokens;
 use rand::{self, Rng};
 use syn::{
     self,
     Expr,
+    ExprLit,
     Field,
     Fields,
     FieldsNamed,
+    GenericArgument,
+    Generics,
     Ident,
+    ItemEnum,
     ItemStruct,
+    Lit,
+    PredicateType,
+    TypeParam,
+    TypeParamBound,
+    WherePredicate,
     parse,
 };
 #[cfg(feature="unstable")]
 use syn::LitStr;
 use syn::{AngleBracketedGenericArguments, Path, TypePath};
 use syn::PathArguments::AngleBracketed;
-use syn::punctuated::Punctuated;
+use syn::punctuated::{Pair, Punctuated};
 use syn::spanned::Spanned;
 use syn::token::Comma;
 
 use ast::{
     Aggregate,
     Join,
-    TypedField,
 };
-use attribute::{field_ty_to_type, fields_vec_to_hashmap};
+use attribute::{
+    FieldAttributes,
+    ReferentialAction,
+    TableAttributes,
+    column_name,
+    field_attributes,
+    field_ty_to_type,
+    fields_vec_to_hashmap,
+    sql_type_attributes,
+    table_attributes,
+    table_name,
+};
 use error::{Error, Result, res};
 use plugin::{new_ident, string_literal};
 #[cfg(feature = "postgres")]
@@ -62,14 +82,20 @@ use self::postgres::create_backend;
 use self::sqlite::create_backend;
 #[cfg(not(any(feature = "rusqlite", feature = "postgres")))]
 use self::dummy::create_backend;
-use sql::fields_to_sql;
+use sql::{
+    self,
+    column_type_to_sql,
+    constraints_to_sql,
+    current_timestamp,
+    foreign_key_to_sql,
+    sep_by,
+    table_constraints_to_sql,
+};
 use state::SqlFields;
-use string::token_to_string;
+use string::{pluralize, to_snake_case, token_to_string};
 use types::{
     Type,
-    get_type_parameter,
     get_type_parameter_as_path,
-    type_to_sql,
 };
 use {
     Arguments,
@@ -83,46 +109,125 @@ use {
 /// Create the from_row() method for the table struct.
 pub fn table_methods(item_struct: &ItemStruct) -> Tokens {
     let table_ident = &item_struct.ident;
+    let generics = &item_struct.generics;
     if let Fields::Named(FieldsNamed { ref named , .. }) = item_struct.fields {
-        let field_idents = named.iter()
-            .map(|field| field.ty.clone());
-        let index = &mut 0;
-        let columns = field_idents.map(|typ| to_row_get(typ, false, index));
+        let index = &mut ColumnIndex::default();
+        let columns = named.iter().map(|field| to_row_get(field, generics, false, index));
 
-        let field_idents = named.iter()
-            .map(|field| field.ty.clone());
-        let index = &mut 0;
-        let related_columns = field_idents.map(|typ| to_row_get(typ, true, index));
-
-        let field_count = named.iter()
-            .filter(|field| {
-                let typ = token_to_string(&field.ty);
-                !typ.starts_with("ForeignKey")
-            })
-            .count();
+        let index = &mut ColumnIndex::default();
+        let related_columns = named.iter().map(|field| to_row_get(field, generics, true, index));
+
+        let mut field_count = ColumnIndex::default();
+        for field in named {
+            field_count.skip(field);
+        }
+        let field_count = field_count.to_tokens();
         let backend = create_backend();
-        let field_count = backend.int_literal(field_count);
 
         let field_idents = named.iter()
             .map(|field| field.ident.clone().expect("field has name"));
         let field_idents2 = named.iter()
             .map(|field| field.ident.clone().expect("field has name"));
         
+        let table_attributes = table_attributes(&item_struct.attrs).unwrap_or_default();
+        let primary_key_fields = primary_key_fields(named, &table_attributes);
+        let primary_key_types = primary_key_fields.iter()
+            .map(|field| &field.ty)
+            .collect::<Vec<_>>();
+        let primary_key_type =
+            match primary_key_types.len() {
+                0 => quote! { i32 }, // NOTE: the query returns -1 when the table has no primary key.
+                1 => {
+                    let typ = primary_key_types[0];
+                    quote! { #typ }
+                },
+                _ => {
+                    let primary_key_types = &primary_key_types;
+                    quote! { (#(#primary_key_types),*) }
+                },
+            };
+        let primary_key_values = (0..primary_key_types.len().max(1))
+            .map(|index| {
+                let index = backend.int_literal(index);
+                quote! { __tql_item_row.get(#index) }
+            });
+        let primary_key_value =
+            if primary_key_types.len() > 1 {
+                quote! { (#(#primary_key_values),*) }
+            }
+            else {
+                quote! { #(#primary_key_values)* }
+            };
+
+        let many_to_many_fields: Vec<_> = named.iter()
+            .filter(|field| is_many_to_many(field))
+            .map(|field| field.ident.clone().expect("field has name"))
+            .collect();
+        let merge_rows =
+            if many_to_many_fields.is_empty() {
+                quote! { items }
+            }
+            else {
+                let many_to_many_fields = &many_to_many_fields;
+                let many_to_many_fields2 = many_to_many_fields;
+                quote! {
+                    ::tql::merge_rows(items, |item, other| {
+                        #(::tql::merge_related(&mut item.#many_to_many_fields, other.#many_to_many_fields2);)*
+                    })
+                }
+            };
+        // NOTE: the primary key of a row missing from a left join is NULL.
+        let is_related_row_null =
+            match primary_key_fields.first() {
+                Some(primary_key_field) => {
+                    let mut index = ColumnIndex::default();
+                    for field in named.iter().take_while(|field| field.ident != primary_key_field.ident) {
+                        index.skip(field);
+                    }
+                    let index = index.to_tokens();
+                    let typ = &primary_key_field.ty;
+                    quote! {
+                        let primary_key: Option<#typ> = __tql_item_row.get(#index + delta);
+                        primary_key.is_none()
+                    }
+                },
+                None => quote! { false },
+            };
+        let primary_key_idents = primary_key_fields.iter()
+            .map(|field| &field.ident);
+        let primary_key =
+            match primary_key_fields.len() {
+                0 => quote! { -1 },
+                1 => quote! { #(self.#primary_key_idents.clone())* },
+                _ => quote! { (#(self.#primary_key_idents.clone()),*) },
+            };
+
         let trait_ident = quote_spanned! { table_ident.span() =>
             ::tql::SqlTable
         };
         let row_type_ident = backend.row_type_ident(&table_ident);
         let delta_type = backend.delta_type();
         let row_ident = Ident::new("__tql_item_row", Span::call_site());
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
         quote! {
-            unsafe impl #trait_ident for #table_ident {
+            unsafe impl #impl_generics #trait_ident for #table_ident #ty_generics #where_clause {
                 const FIELD_COUNT: #delta_type = #field_count;
 
+                type PrimaryKey = #primary_key_type;
+
                 fn _tql_default() -> Self {
                     unimplemented!()
                 }
 
+                fn merge_rows(items: Vec<Self>) -> Vec<Self> {
+                    #merge_rows
+                }
+
+                fn primary_key(&self) -> Self::PrimaryKey {
+                    #primary_key
+                }
+
                 #[allow(unused)]
                 fn from_row(#row_ident: &#row_type_ident) -> Self {
                     Self {
@@ -136,6 +241,15 @@ pub fn table_methods(item_struct: &ItemStruct) -> Tokens {
                         #(#field_idents2: #related_columns,)*
                     }
                 }
+
+                #[allow(unused)]
+                fn is_related_row_null(#row_ident: &#row_type_ident, delta: #delta_type) -> bool {
+                    #is_related_row_null
+                }
+
+                fn primary_key_from_row(#row_ident: &#row_type_ident) -> Self::PrimaryKey {
+                    #primary_key_value
+                }
             }
         }
     }
@@ -150,31 +264,327 @@ pub fn tosql_impl(item_struct: &ItemStruct, primary_key_field: Option<String>) -
     let table_ident = &item_struct.ident;
     let debug_impl = create_debug_impl(item_struct);
     let backend = create_backend();
+    let composite_primary_key = !table_attributes(&item_struct.attrs).unwrap_or_default().primary_key.is_empty();
+    let mut primary_key_type = None;
     let to_sql_code =
         if let Some(pk) = primary_key_field {
             let primary_key_ident = Ident::new(&pk, Span::call_site());
+            if let Fields::Named(FieldsNamed { ref named , .. }) = item_struct.fields {
+                primary_key_type = named.iter()
+                    .find(|field| field.ident.as_ref() == Some(&primary_key_ident))
+                    .map(|field| &field.ty);
+            }
             backend.to_sql(&primary_key_ident)
         }
+        else if composite_primary_key {
+            quote! {
+                panic!("The primary key of table {} has multiple columns, so it cannot be converted to a single value",
+                       stringify!(#table_ident));
+            }
+        }
         else {
             quote! {
                 panic!("No primary key for table {}", stringify!(#table_ident));
             }
         };
     let to_owned_ident = Ident::new("to_owned", Span::call_site());
-    let code = backend.to_sql_impl(table_ident, to_sql_code);
+    // NOTE: the ToSql trait of some backends requires the Debug trait.
+    let debug_generics = debug_generics(&item_struct.generics);
+    let code = backend.to_sql_impl(table_ident, &debug_generics, primary_key_type, to_sql_code);
+    let (impl_generics, ty_generics, where_clause) = item_struct.generics.split_for_impl();
     quote! {
         #debug_impl
         #code
 
-        impl #table_ident {
+        impl #impl_generics #table_ident #ty_generics #where_clause {
             #[allow(dead_code)]
-            pub fn #to_owned_ident(&self) -> Option<Self> {
+            pub fn #to_owned_ident<__TqlForeignKey: From<Option<Box<Self>>>>(&self) -> __TqlForeignKey {
                 unimplemented!();
             }
         }
     }
 }
 
+/// Add the implementations for a struct with `#[derive(SqlType)]`: the `SqlType` trait when it is
+/// transparent, the conversions of the backend and the macro giving its SQL type.
+pub fn sql_type_impl(item_struct: &ItemStruct) -> Result<Tokens> {
+    let type_ident = &item_struct.ident;
+    let mut errors = vec![];
+    let attributes = sql_type_attributes(&item_struct.attrs)?;
+    let mut sql_type_trait_impl = quote! {};
+    let repr =
+        if attributes.transparent {
+            if attributes.repr.is_some() {
+                errors.push(Error::new("the repr attribute cannot be used with #[tql(transparent)]",
+                                       item_struct.ident.span()));
+            }
+            let mut fields = item_struct.fields.iter();
+            match (fields.next(), fields.next()) {
+                (Some(field), None) => {
+                    let repr = &field.ty;
+                    let (to_repr, from_repr) =
+                        match field.ident {
+                            Some(ref field_ident) => (quote! { self.#field_ident.clone() },
+                                                      quote! { #type_ident { #field_ident: repr } }),
+                            None => (quote! { self.0.clone() }, quote! { #type_ident(repr) }),
+                        };
+                    sql_type_trait_impl = quote! {
+                        impl ::tql::SqlType for #type_ident {
+                            type Repr = #repr;
+
+                            fn to_repr(&self) -> #repr {
+                                #to_repr
+                            }
+
+                            fn from_repr(repr: #repr) ->
+                                Result<Self, Box<::std::error::Error + Sync + Send>>
+                            {
+                                Ok(#from_repr)
+                            }
+                        }
+                    };
+                    Some(repr.clone())
+                },
+                _ => {
+                    errors.push(Error::new("#[tql(transparent)] can only be used on a struct with a single field",
+                                           item_struct.ident.span()));
+                    None
+                },
+            }
+        }
+        else if let Some(repr) = attributes.repr {
+            // NOTE: the SqlType trait is implemented manually, so check that it uses the same representation.
+            let struct_ident = new_ident(&format!("CheckRepr{}", rand_string()));
+            sql_type_trait_impl = quote_spanned! { repr.span() =>
+                #[allow(dead_code)]
+                struct #struct_ident where #type_ident: ::tql::SqlType<Repr = #repr> {
+                    field: #type_ident,
+                }
+            };
+            Some(repr)
+        }
+        else {
+            let mut error = Error::new("missing representation of the SqlType", item_struct.ident.span());
+            error.add_help("use #[tql(transparent)] on a single-field struct or #[tql(repr = \"…\")]");
+            errors.push(error);
+            None
+        };
+
+    let repr =
+        match repr {
+            Some(repr) => repr,
+            None => return Err(errors),
+        };
+    let typ = field_ty_to_type(&repr);
+    // NOTE: the type parsed from the repr attribute has no position in the source.
+    let span = if attributes.transparent { typ.span } else { item_struct.ident.span() };
+    match typ.node {
+        Type::Array(_) | Type::Custom(_) | Type::Duration | Type::Generic | Type::IpAddr | Type::ManyToMany(_) |
+            Type::Nullable(_) | Type::PrimaryKey(_) | Type::U16 | Type::U32 | Type::U64 | Type::UnsupportedType(_) =>
+            errors.push(Error::new_with_code(
+                &format!("use of unsupported type name `{}`", token_to_string(&repr)), span, "E0412")),
+        _ => (),
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let sql_type = sql::type_to_sql(&typ.node, true);
+    let macro_name = Ident::new(&format!("tql_{}_sql_type", type_ident), Span::call_site());
+    let conversions = create_backend().sql_type_conversions(type_ident, &repr);
+    Ok(quote! {
+        #sql_type_trait_impl
+        #conversions
+
+        #[macro_export]
+        macro_rules! #macro_name {
+            () => { #sql_type };
+            (check $column:expr) => { "" };
+            (create) => { "" };
+        }
+    })
+}
+
+/// Add the implementations for an enum with `#[derive(SqlEnum)]`: the `SqlType` trait, the conversions
+/// of the backend and the macro giving its SQL type.
+pub fn sql_enum_impl(item_enum: &ItemEnum) -> Result<Tokens> {
+    let type_ident = &item_enum.ident;
+    let type_name = type_ident.to_string();
+    let mut errors = vec![];
+    for variant in &item_enum.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            errors.push(Error::new("the variants of a SqlEnum cannot have fields", variant.fields.span()));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let variants: Vec<_> = item_enum.variants.iter()
+        .map(|variant| variant.ident.clone())
+        .collect();
+    let variant_names: Vec<_> = variants.iter()
+        .map(ToString::to_string)
+        .collect();
+    let sql_type_trait_impl = {
+        let paths1 = variants.iter().map(|variant| quote! { #type_ident::#variant });
+        let paths2 = paths1.clone();
+        let names1 = &variant_names;
+        let names2 = &variant_names;
+        quote! {
+            impl ::tql::SqlType for #type_ident {
+                type Repr = String;
+
+                fn to_repr(&self) -> String {
+                    match *self {
+                        #(#paths1 => #names1.to_string(),)*
+                    }
+                }
+
+                fn from_repr(repr: String) -> Result<Self, Box<::std::error::Error + Sync + Send>> {
+                    match repr.as_str() {
+                        #(#names2 => Ok(#paths2),)*
+                        _ => Err(format!("unknown variant {} of {}", repr, #type_name).into()),
+                    }
+                }
+            }
+        }
+    };
+
+    let sql_enum = sql::enum_to_sql(&type_name, &variant_names);
+    let column_type = sql_enum.column_type;
+    let check =
+        match sql_enum.check {
+            Some(check) => {
+                let check = format!(" {})", check);
+                quote! { concat!(" CHECK (", $column, #check) }
+            },
+            None => quote! { "" },
+        };
+    let create_query = sql_enum.create_query.unwrap_or_default();
+    let macro_name = Ident::new(&format!("tql_{}_sql_type", type_ident), Span::call_site());
+    let conversions = create_backend().sql_enum_conversions(type_ident, &variants);
+    Ok(quote! {
+        #sql_type_trait_impl
+        #conversions
+
+        #[macro_export]
+        macro_rules! #macro_name {
+            () => { #column_type };
+            (check $column:expr) => { #check };
+            (create) => { #create_query };
+        }
+    })
+}
+
+/// Add the implementations for a struct with `#[derive(SqlEmbed)]`: the `SqlEmbed` trait reading its fields from a
+/// row and the macro giving the SQL of its columns, which are prefixed by the column of the embedded field.
+pub fn sql_embed_impl(item_struct: &ItemStruct) -> Result<Tokens> {
+    let type_ident = &item_struct.ident;
+    let named =
+        match item_struct.fields {
+            Fields::Named(FieldsNamed { ref named , .. }) => named,
+            _ => return Err(vec![Error::new("Expected normal struct, found", item_struct.ident.span())]), // TODO: improve this message.
+        };
+    let mut errors = vec![];
+    for field in named {
+        let attributes =
+            match field_attributes(&field.attrs) {
+                Ok(attributes) => attributes,
+                Err(attribute_errors) => {
+                    errors.extend(attribute_errors);
+                    continue;
+                },
+            };
+        if attributes.auto_now || attributes.auto_now_add || attributes.check.is_some() || attributes.embed ||
+            !attributes.foreign_key.is_empty() || attributes.max_length.is_some() ||
+            attributes.on_delete.is_some() || attributes.on_update.is_some() || attributes.related_name.is_some() ||
+            attributes.through.is_some() || attributes.validate.is_some()
+        {
+            errors.push(Error::new("only the column, precision, scale and unique attributes can be used in an embedded struct",
+                                   field.span()));
+        }
+        check_decimal_attributes(field, &attributes, &mut errors);
+        let typ = field_ty_to_type(&field.ty);
+        let inner_type =
+            match typ.node {
+                Type::Nullable(ref inner_type) => &**inner_type,
+                ref typ => typ,
+            };
+        match *inner_type {
+            #[cfg(not(feature = "postgres"))]
+            Type::Array(_) => errors.push(array_error(typ.span)),
+            Type::Custom(_) | Type::Generic | Type::ManyToMany(_) | Type::Nullable(_) | Type::PrimaryKey(_) |
+                Type::UnsupportedType(_) | Type::UserDefined(_) =>
+                errors.push(Error::new_with_code(
+                    &format!("use of unsupported type name `{}` in an embedded struct", token_to_string(&field.ty)),
+                    typ.span, "E0412")),
+            _ => (),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let backend = create_backend();
+    let delta_type = backend.delta_type();
+    let row_type_ident = backend.row_type_ident(type_ident);
+    let row_ident = Ident::new("__tql_item_row", Span::call_site());
+    let field_count = backend.int_literal(named.len());
+    let field_idents = named.iter()
+        .map(|field| &field.ident);
+    let index = &mut ColumnIndex::default();
+    let columns: Vec<_> = named.iter()
+        .map(|field| to_row_get(field, &item_struct.generics, true, index))
+        .collect();
+
+    let mut column_definitions = vec![];
+    let mut field_list = vec![];
+    let mut column_patterns = vec![];
+    for field in named {
+        let attributes = field_attributes(&field.attrs).unwrap_or_default();
+        let ident = &field.ident;
+        let column = column_name(field);
+        let typ = column_type_to_sql(&field_ty_to_type(&field.ty).node, &attributes);
+        let constraints = constraints_to_sql(&attributes);
+        column_definitions.push(quote! { $prefix, #column, " ", #typ, #constraints });
+        field_list.push(quote! { $table, ".", $prefix, #column });
+        column_patterns.push(quote! {
+            (column $prefix:expr, #ident) => { concat!($prefix, #column) };
+        });
+    }
+    let column_definitions = sep_by(column_definitions.into_iter(), ", ");
+    let field_list = sep_by(field_list.into_iter(), ", ");
+    let converted_fields = named.iter()
+        .filter(|field| is_converted(&field.ty))
+        .map(|field| &field.ident);
+    let macro_name = Ident::new(&format!("tql_{}_embedded", type_ident), Span::call_site());
+    Ok(quote! {
+        impl ::tql::SqlEmbed for #type_ident {
+            const FIELD_COUNT: #delta_type = #field_count;
+
+            #[allow(unused)]
+            fn from_row(#row_ident: &#row_type_ident, delta: #delta_type) -> Self {
+                Self {
+                    #(#field_idents: #columns,)*
+                }
+            }
+        }
+
+        #[macro_export]
+        macro_rules! #macro_name {
+            (create $prefix:expr) => { concat!(#column_definitions) };
+            (field_list $table:expr, $prefix:expr) => { concat!(#field_list) };
+            #(#column_patterns)*
+            // NOTE: the check for the field name is done elsewhere, hence it is okay to return
+            // "" here.
+            (column $prefix:expr, $field:ident) => { "" };
+            #((argument #converted_fields, $argument:expr) => { &::tql::Convert::to_wrapper($argument) };)*
+            (argument $field:ident, $argument:expr) => { $argument };
+        }
+    })
+}
+
 fn create_debug_impl(item_struct: &ItemStruct) -> Tokens {
     let table_ident = &item_struct.ident;
     let table_name = table_ident.to_string();
@@ -188,8 +598,10 @@ fn create_debug_impl(item_struct: &ItemStruct) -> Tokens {
         let std_ident = quote_spanned! { table_ident.span() =>
             ::std
         };
+        let generics = debug_generics(&item_struct.generics);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
         quote! {
-            impl #std_ident::fmt::Debug for #table_ident {
+            impl #impl_generics #std_ident::fmt::Debug for #table_ident #ty_generics #where_clause {
                 fn fmt(&self, formatter: &mut #std_ident::fmt::Formatter) -> Result<(), #std_ident::fmt::Error> {
                     formatter.debug_struct(#table_name)
                         #(.field(#field_names, &self.#field_idents))*
@@ -226,31 +638,57 @@ pub fn generate_errors(errors: Vec<Error>) -> TokenStream {
 
 /// Generate the Rust code from the SQL query.
 pub(crate) fn gen_query(args: &SqlQueryWithArgs, connection_expr: Tokens) -> (TokenStream, Vec<Tokens>) {
-    let struct_expr = create_struct(&args.table_name, &args.joins);
+    let struct_expr = create_struct(&args.table_name, &args.table_type, &args.joins);
     let (aggregate_struct, aggregate_expr) = gen_aggregate_struct(&args.aggregates);
-    let (args_expr, metavars) = typecheck_arguments(args);
+    let (args_expr, metavars, bindings, validations) = typecheck_arguments(args);
     let backend = create_backend();
     let tokens = backend.gen_query_expr(connection_expr, args, args_expr, struct_expr, aggregate_struct,
                                         aggregate_expr);
+    // NOTE: the values assigned by an insert or an update are validated before the query is sent.
+    let tokens =
+        if validations.is_empty() {
+            tokens
+        }
+        else {
+            quote! {{
+                #(#bindings)*
+                let __tql_validation = (|| -> ::std::result::Result<(), ::tql::ValidationError> {
+                    #(#validations?;)*
+                    ::std::result::Result::Ok(())
+                })();
+                match __tql_validation {
+                    ::std::result::Result::Ok(()) => #tokens,
+                    ::std::result::Result::Err(error) =>
+                        ::std::result::Result::Err(::std::convert::From::from(error)),
+                }
+            }}
+        };
     (tokens.into(), metavars)
 }
 
 /// Create the struct expression needed by the generated code.
-fn create_struct(table_ident: &Ident, joins: &[Join]) -> Tokens {
+fn create_struct(table_ident: &Ident, table_type: &Tokens, joins: &[Join]) -> Tokens {
     let row_ident = quote! { __tql_item_row };
     let assign_related_fields =
         joins.iter()
             .map(|join| {
                 let ident = &join.base_field;
                 quote_spanned! { ident.span() => {
-                    let ref mut _related_field: Option<_> = item.#ident;
+                    let _related_field = &mut item.#ident;
                     _tql_delta += ::tql::from_related_row(_related_field, &#row_ident, _tql_delta);
                 }}
             });
+    // NOTE: the field count is only needed by the joins and it would prevent inferring the type
+    // parameters of the table from the result type.
+    if joins.is_empty() {
+        return quote_spanned! { table_ident.span() =>
+            <#table_type as ::tql::SqlTable>::from_row(&#row_ident)
+        };
+    }
     quote_spanned! { table_ident.span() => {
         #[allow(unused_mut)]
-        let mut item = <#table_ident as ::tql::SqlTable>::from_row(&#row_ident);
-        let mut _tql_delta = <#table_ident as ::tql::SqlTable>::FIELD_COUNT;
+        let mut item = <#table_type as ::tql::SqlTable>::from_row(&#row_ident);
+        let mut _tql_delta = <#table_type as ::tql::SqlTable>::FIELD_COUNT;
         #(#assign_related_fields)*
         item
     }}
@@ -282,7 +720,6 @@ fn gen_aggregate_struct(aggregates: &[Aggregate]) -> (Tokens, Tokens) {
     }})
 }
 
-
 /// Get the fields from the struct (also returns the ToSql implementations to check that the types
 /// used for ForeignKey have a #[derive(SqlTable)]).
 /// Also check if the field types from the struct are supported types.
@@ -292,6 +729,66 @@ pub fn get_struct_fields(item_struct: &ItemStruct) -> (Result<SqlFields>, Option
             span, "E0412")
     }
 
+    // NOTE: these types are only recognized with their full path, so that a user-defined type can
+    // have the same name. A warning is a compile error without the unstable feature, so the hint is
+    // only given with this feature.
+    fn unqualified_type_warning(span: Span, typ: &str) -> Option<Error> {
+        if cfg!(not(feature = "unstable")) {
+            return None;
+        }
+        let path =
+            match typ {
+                "Decimal" => "rust_decimal::Decimal",
+                "Duration" => "chrono::Duration",
+                "Value" => "serde_json::Value",
+                _ => return None,
+            };
+        let mut warning = Error::new_warning(&format!("the type `{}` is used as a user-defined SqlType", typ), span);
+        warning.add_help(&format!("write the type of the field as {} to use the type supported by tql", path));
+        Some(warning)
+    }
+
+    // TODO: replace with a trait bound on ForeignKey when it is stable.
+    fn check_table<T: ToTokens>(typ: &T, span: Span) -> TokenStream {
+        let struct_ident = new_ident(&format!("CheckForeignKey{}", rand_string()));
+        quote_spanned!{ span =>
+            #[allow(dead_code)]
+            struct #struct_ident where #typ: ::tql::SqlTable {
+                field: #typ,
+            }
+        }.into()
+    }
+
+    fn check_sql_type(typ: &str, span: Span) -> TokenStream {
+        let type_ident = new_ident(typ);
+        let struct_ident = new_ident(&format!("CheckSqlType{}", rand_string()));
+        quote_spanned!{ span =>
+            #[allow(dead_code)]
+            struct #struct_ident where #type_ident: ::tql::SqlType {
+                field: #type_ident,
+            }
+        }.into()
+    }
+
+    fn check_embedded(typ: &str, span: Span) -> TokenStream {
+        let type_ident = new_ident(typ);
+        let struct_ident = new_ident(&format!("CheckEmbedded{}", rand_string()));
+        quote_spanned!{ span =>
+            #[allow(dead_code)]
+            struct #struct_ident where #type_ident: ::tql::SqlEmbed {
+                field: #type_ident,
+            }
+        }.into()
+    }
+
+    fn check_field_exists(ident: &Ident, fields: &[Field], item_struct: &ItemStruct, errors: &mut Vec<Error>) {
+        if !fields.iter().any(|field| field.ident.as_ref() == Some(ident)) {
+            errors.push(Error::new_with_code(
+                &format!("no field `{}` on type `{}`", ident, item_struct.ident),
+                ident.span(), "E0609"));
+        }
+    }
+
     let mut primary_key_field = None;
     let position = item_struct.ident.span();
     let mut impls: TokenStream = quote! {}.into();
@@ -302,54 +799,204 @@ pub fn get_struct_fields(item_struct: &ItemStruct) -> (Result<SqlFields>, Option
             Fields::Named(FieldsNamed { ref named , .. }) => named.into_iter().cloned().collect(),
             _ => return (Err(vec![Error::new("Expected normal struct, found", position)]), None, empty_token_stream()), // TODO: improve this message.
         };
+    let mut composite_primary_key = false;
+    match table_attributes(&item_struct.attrs) {
+        Ok(attributes) => {
+            composite_primary_key = !attributes.primary_key.is_empty();
+            for ident in attributes.primary_key.iter().chain(attributes.unique_together.iter().flatten()) {
+                check_field_exists(ident, &fields, item_struct, &mut errors);
+            }
+            if attributes.soft_delete && soft_delete_field(&fields, &attributes).is_none() {
+                let mut error = Error::new("a table with #[tql(soft_delete)] needs a `deleted_at` field", position);
+                error.add_help("add the field `deleted_at: Option<DateTime<Utc>>`");
+                errors.push(error);
+            }
+            // NOTE: SQLite does not support a schema in the REFERENCES clause of a foreign key.
+            if attributes.schema.is_some() && !cfg!(feature = "postgres") {
+                errors.push(Error::new("the schema attribute is only supported with PostgreSQL", position));
+            }
+        },
+        Err(attribute_errors) => errors.extend(attribute_errors),
+    }
+
     let mut primary_key_count = 0;
+    let mut many_to_many_count = 0;
     for field in &fields {
+        match field_attributes(&field.attrs) {
+            Ok(attributes) => {
+                for ident in &attributes.foreign_key {
+                    check_field_exists(ident, &fields, item_struct, &mut errors);
+                }
+                if !attributes.foreign_key.is_empty() && foreign_key_table(field).is_none() {
+                    errors.push(Error::new("the foreign_key attribute can only be used on a ForeignKey field",
+                                           field.ty.span()));
+                }
+                if attributes.related_name.is_some() && foreign_key_table(field).is_none() {
+                    errors.push(Error::new("the related_name attribute can only be used on a ForeignKey field",
+                                           field.ty.span()));
+                }
+                if attributes.on_delete.is_some() || attributes.on_update.is_some() {
+                    if foreign_key_table(field).is_none() {
+                        errors.push(Error::new("the on_delete and on_update attributes can only be used on a ForeignKey field",
+                                               field.ty.span()));
+                    }
+                    else if !is_nullable_foreign_key(field) &&
+                        (attributes.on_delete == Some(ReferentialAction::SetNull) ||
+                         attributes.on_update == Some(ReferentialAction::SetNull))
+                    {
+                        let mut error = Error::new("the set_null action needs a nullable foreign key", field.ty.span());
+                        error.add_help("use Option<ForeignKey<…>> for this field");
+                        errors.push(error);
+                    }
+                }
+                check_decimal_attributes(field, &attributes, &mut errors);
+                check_auto_now_attributes(field, &attributes, &mut errors);
+                check_max_length_attribute(field, &attributes, &mut errors);
+                if attributes.validate.is_some() &&
+                    (attributes.embed || foreign_key_table(field).is_some() || is_many_to_many(field))
+                {
+                    errors.push(Error::new("the validate attribute cannot be used on a ForeignKey, ManyToMany or embedded field",
+                                           field.ty.span()));
+                }
+                if let Some(ref through) = attributes.through {
+                    if is_many_to_many(field) {
+                        let struct_ident = new_ident(&format!("CheckThrough{}", rand_string()));
+                        let code: TokenStream = quote_spanned! { through.span() =>
+                            #[allow(dead_code)]
+                            struct #struct_ident where #through: ::tql::SqlTable {
+                                field: #through,
+                            }
+                        }.into();
+                        impls = concat_token_stream(impls, code);
+                    }
+                    else {
+                        errors.push(Error::new("the through attribute can only be used on a ManyToMany field",
+                                               field.ty.span()));
+                    }
+                }
+                if attributes.embed {
+                    match field_ty_to_type(&field.ty).node {
+                        Type::UserDefined(ref typ) => {
+                            if attributes.unique || attributes.check.is_some() {
+                                errors.push(Error::new("the unique and check attributes cannot be used on an embedded field",
+                                                       field.ty.span()));
+                            }
+                            impls = concat_token_stream(impls, check_embedded(typ, field.ty.span()));
+                            continue;
+                        },
+                        Type::Nullable(ref typ) if matches!(**typ, Type::UserDefined(_)) => {
+                            let mut error = Error::new("an embedded field cannot be nullable", field.ty.span());
+                            error.add_help("use Option<…> for the fields of the embedded struct instead");
+                            errors.push(error);
+                            continue;
+                        },
+                        _ => errors.push(Error::new("the embed attribute can only be used on a field whose type has #[derive(SqlEmbed)]",
+                                                    field.ty.span())),
+                    }
+                }
+            },
+            Err(attribute_errors) => errors.extend(attribute_errors),
+        }
         if let Some(ref field_ident) = field.ident {
             let field_type = &field.ty;
             let field_name = field_ident.to_string();
+            let param = type_parameter(field_type, &item_struct.generics);
             let field = field_ty_to_type(&field.ty);
             match field.node {
+                // NOTE: the SqlType bound of a type parameter gives the type of its column.
+                Type::UserDefined(_) if param.is_some() => {
+                    let param = param.expect("type parameter");
+                    match type_parameter_repr(param, &item_struct.generics) {
+                        Some(repr) => match field_ty_to_type(repr).node {
+                            Type::Array(_) | Type::Custom(_) | Type::Duration | Type::Generic | Type::IpAddr |
+                                Type::ManyToMany(_) | Type::Nullable(_) | Type::PrimaryKey(_) | Type::U16 | Type::U32 |
+                                Type::U64 | Type::UnsupportedType(_) =>
+                                errors.push(Error::new_with_code(
+                                    &format!("use of unsupported type name `{}`", token_to_string(repr)), repr.span(),
+                                    "E0412")),
+                            _ => (),
+                        },
+                        None => {
+                            let mut error = Error::new(
+                                &format!("the type parameter `{}` has no SqlType bound giving the type of the column",
+                                         param.ident), field.span);
+                            error.add_help(&format!("add the bound `{}: SqlType<Repr = …>`", param.ident));
+                            errors.push(error);
+                        },
+                    }
+                },
                 Type::Nullable(ref inner_type) => {
-                    if let Type::UnsupportedType(ref typ) = **inner_type {
-                        errors.push(error(field.span, typ));
+                    match **inner_type {
+                        #[cfg(not(feature = "postgres"))]
+                        Type::Array(_) => errors.push(array_error(field.span)),
+                        Type::UnsupportedType(ref typ) => errors.push(error(field.span, typ)),
+                        // NOTE: the check cannot use the type parameters of the struct.
+                        Type::Custom(_) => {
+                            let path = foreign_key_path(field_type).expect("ForeignKey inner type");
+                            if !uses_type_parameters(path, &item_struct.generics) {
+                                impls = concat_token_stream(impls, check_table(path, path.span()));
+                            }
+                        },
+                        Type::UserDefined(ref typ) if item_struct.generics.type_params().any(|param| param.ident == typ) =>
+                            errors.push(Error::new("a field whose type is a type parameter cannot be nullable",
+                                                   field.span)),
+                        Type::UserDefined(ref typ) => {
+                            errors.extend(unqualified_type_warning(field.span, typ));
+                            impls = concat_token_stream(impls, check_sql_type(typ, field.span));
+                        },
+                        _ => (),
                     }
                 },
+                Type::UserDefined(ref typ) => {
+                    errors.extend(unqualified_type_warning(field.span, typ));
+                    impls = concat_token_stream(impls, check_sql_type(typ, field.span));
+                },
+                #[cfg(not(feature = "postgres"))]
+                Type::Array(_) => errors.push(array_error(field.span)),
                 Type::UnsupportedType(ref typ) =>
                     errors.push(error(field.span, typ)),
                 // NOTE: Other types are supported.
-                Type::Serial => {
+                Type::PrimaryKey(ref inner_type) => {
+                    match **inner_type {
+                        Type::Array(_) | Type::Custom(_) | Type::Duration | Type::Generic | Type::IpAddr |
+                            Type::Nullable(_) | Type::PrimaryKey(_) | Type::U16 | Type::U32 | Type::U64 |
+                            Type::UnsupportedType(_) =>
+                            errors.push(Error::new_with_code(
+                                &format!("use of unsupported primary key type `{}`", inner_type), field.span,
+                                "E0412")),
+                        Type::UserDefined(ref typ) => {
+                            errors.extend(unqualified_type_warning(field.span, typ));
+                            impls = concat_token_stream(impls, check_sql_type(typ, field.span));
+                        },
+                        _ => (),
+                    }
                     primary_key_field = Some(field_name);
                     primary_key_count += 1;
                 },
-                Type::Custom(ref typ) => {
-                    let type_ident = new_ident(typ);
-                    let struct_ident = new_ident(&format!("CheckForeignKey{}", rand_string()));
-                    // TODO: replace with a trait bound on ForeignKey when it is stable.
-                    let span = {
+                Type::Custom(_) | Type::ManyToMany(_) => {
+                    if let Type::ManyToMany(_) = field.node {
+                        many_to_many_count += 1;
+                    }
+                    let related_type = {
                         if let syn::Type::Path(TypePath { path: Path { ref segments, .. }, ..}) = *field_type {
                             let segment = segments.first().expect("first segment").into_value();
                             if let AngleBracketed(AngleBracketedGenericArguments { ref args, .. }) =
                                 segment.arguments
                             {
-                                args.first().expect("first argument").span()
+                                let argument = args.first().expect("first argument").into_value();
+                                quote! { #argument }
                             }
                             else {
-                                field_type.span()
+                                quote! { #field_type }
                             }
                         }
                         else {
-                            field_type.span()
+                            quote! { #field_type }
                         }
                     };
-
-                    let code: TokenStream = quote_spanned!{ span =>
-                        #[allow(dead_code)]
-                        struct #struct_ident where #type_ident: ::tql::SqlTable {
-                            field: #type_ident,
-                        }
-                    }.into();
-
-                    impls = concat_token_stream(impls, code);
+                    if !uses_type_parameters(&related_type, &item_struct.generics) {
+                        impls = concat_token_stream(impls, check_table(&related_type, related_type.span()));
+                    }
                 },
                 _ => (),
             }
@@ -357,85 +1004,260 @@ pub fn get_struct_fields(item_struct: &ItemStruct) -> (Result<SqlFields>, Option
     }
 
     match primary_key_count {
+        0 if composite_primary_key => (), // The primary key is specified in the attribute.
         0 => errors.insert(0, Error::new_warning("No primary key found", position)),
+        _ if composite_primary_key =>
+            errors.insert(0, Error::new("A PrimaryKey field cannot be used with #[tql(primary_key(…))]", position)),
         1 => (), // One primary key is OK.
-        _ => errors.insert(0, Error::new_warning("More than one primary key is currently not supported", position)),
+        _ => {
+            let mut error = Error::new_warning("More than one primary key is currently not supported", position);
+            error.add_help("use #[tql(primary_key(…))] on the struct for a primary key with multiple columns");
+            errors.insert(0, error);
+        },
+    }
+
+    if many_to_many_count > 0 && (composite_primary_key || primary_key_count != 1) {
+        errors.push(Error::new("a table with a ManyToMany field needs a primary key with a single column",
+                               position));
     }
 
     let fields = fields_vec_to_hashmap(&fields);
     (res(fields, errors), primary_key_field, impls)
 }
 
-fn field_list_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
-    let field_list = named.iter()
-        .filter(|field| {
-            let typ = token_to_string(&field.ty);
-            !typ.starts_with("ForeignKey")
-        })
-        .map(|field| {
-            format!("{table}.{column}",
-                    column = field.ident.clone().expect("field has name"),
-                    table = table_ident
-                   )
-        })
-        .collect::<Vec<_>>()
-        .join(", ");
-    let field_list = string_literal(&field_list);
+#[cfg(not(feature = "postgres"))]
+fn array_error(span: Span) -> Error {
+    let mut error = Error::new("array columns are only supported with PostgreSQL", span);
+    error.add_help("use Json<Vec<…>> to store the elements in a JSON document");
+    error
+}
+
+/// Check that the precision and scale attributes are used on a `Decimal` field with valid values.
+fn check_decimal_attributes(field: &Field, attributes: &FieldAttributes, errors: &mut Vec<Error>) {
+    if attributes.precision.is_none() && attributes.scale.is_none() {
+        return;
+    }
+    if !is_decimal(field) {
+        let mut error = Error::new("the precision and scale attributes can only be used on a Decimal field",
+                                   field.ty.span());
+        error.add_help("write the type of the field as rust_decimal::Decimal");
+        errors.push(error);
+        return;
+    }
+    match (attributes.precision, attributes.scale) {
+        (None, Some(_)) =>
+            errors.push(Error::new("the scale attribute needs the precision attribute", field.ty.span())),
+        (Some(0), _) =>
+            errors.push(Error::new("the precision must be at least 1", field.ty.span())),
+        (Some(precision), Some(scale)) if scale > precision =>
+            errors.push(Error::new(&format!("the scale ({}) cannot be greater than the precision ({})",
+                                            scale, precision), field.ty.span())),
+        _ => (),
+    }
+}
+
+/// Check that the max_length attribute is used on a `String` field with a valid value.
+fn check_max_length_attribute(field: &Field, attributes: &FieldAttributes, errors: &mut Vec<Error>) {
+    match attributes.max_length {
+        Some(_) if !is_string(field) =>
+            errors.push(Error::new("the max_length attribute can only be used on a String field", field.ty.span())),
+        Some(0) =>
+            errors.push(Error::new("the max_length must be at least 1", field.ty.span())),
+        _ => (),
+    }
+}
+
+/// Check that the auto_now and auto_now_add attributes are used on a `DateTime<Utc>` or `NaiveDateTime` field.
+fn check_auto_now_attributes(field: &Field, attributes: &FieldAttributes, errors: &mut Vec<Error>) {
+    if !attributes.auto_now && !attributes.auto_now_add {
+        return;
+    }
+    if attributes.auto_now && attributes.auto_now_add {
+        let mut error = Error::new("the auto_now and auto_now_add attributes cannot be used together",
+                                   field.ty.span());
+        error.add_help("the auto_now attribute also sets the field when the row is inserted");
+        errors.push(error);
+    }
+    if current_timestamp(&field_ty_to_type(&field.ty).node).is_none() {
+        errors.push(Error::new("the auto_now and auto_now_add attributes can only be used on a DateTime<Utc> or NaiveDateTime field",
+                               field.ty.span()));
+    }
+}
+
+/// Create the macro giving the list of columns prefixed by the table name, or by the alias given
+/// as argument for a joined table.
+fn field_list_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident, table_name: &str) -> Tokens {
+    let aliased_field_list = field_list(named, quote! { $alias });
+    let field_list = field_list(named, quote! { #table_name });
     let macro_name = Ident::new(&format!("tql_{}_field_list", table_ident), Span::call_site());
     quote! {
         #[macro_export]
         macro_rules! #macro_name {
-            () => { #field_list };
+            () => { concat!(#field_list) };
+            ($alias:expr) => { concat!(#aliased_field_list) };
         }
     }
 }
 
-fn create_query_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+/// Get the list of the columns selected in a query, prefixed by the `table` name.
+fn field_list(named: &Punctuated<Field, Comma>, table: Tokens) -> Tokens {
+    let columns = named.iter()
+        .filter(|field| is_selected_column(field))
+        .map(|field| {
+            let column = column_name(field);
+            match embedded_struct(field) {
+                Some(typ) => {
+                    let macro_name = Ident::new(&format!("tql_{}_embedded", typ), Span::call_site());
+                    let prefix = format!("{}_", column);
+                    quote! { #macro_name!(field_list #table, #prefix) }
+                },
+                None => quote! { #table, ".", #column },
+            }
+        });
+    sep_by(columns, ", ")
+}
+
+fn create_query_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident, generics: &Generics, table: &str,
+                      table_attributes: &TableAttributes) -> Tokens
+{
     let mut fields_to_create = vec![];
+    let mut foreign_keys = vec![];
     for field in named {
-        fields_to_create.push(TypedField {
-            identifier: field.ident.clone().expect("field ident").to_string(),
-            typ: type_to_sql(&field_ty_to_type(&field.ty).node),
+        // NOTE: the items related by a ManyToMany field are stored in a link table.
+        if is_many_to_many(field) {
+            continue;
+        }
+        if let Some(typ) = embedded_struct(field) {
+            let macro_name = Ident::new(&format!("tql_{}_embedded", typ), Span::call_site());
+            let prefix = format!("{}_", column_name(field));
+            fields_to_create.push(quote! { #macro_name!(create #prefix) });
+            continue;
+        }
+        let attributes = field_attributes(&field.attrs).unwrap_or_default();
+        if !attributes.foreign_key.is_empty() {
+            if let Some(related_table) = foreign_key_table(field) {
+                foreign_keys.push(foreign_key_to_sql(&foreign_key_columns(named, &attributes.foreign_key),
+                                                     &related_table, &attributes));
+            }
+            continue;
+        }
+        let field_type = column_type(field, generics);
+        let typ = column_type_to_sql(&field_type, &attributes);
+        let constraints = constraints_to_sql(&attributes);
+        let column = column_name(field);
+        // NOTE: the column of an enum checks that it stores a variant with some backends.
+        let check =
+            match user_defined_type(&field_type) {
+                Some(typ) => {
+                    let macro_name = Ident::new(&format!("tql_{}_sql_type", typ), Span::call_site());
+                    quote! { #macro_name!(check #column) }
+                },
+                None => quote! { "" },
+            };
+        fields_to_create.push(quote! {
+            #column, " ", #typ, #constraints, #check
         });
     }
-    let table = table_ident.to_string();
-    let fields = fields_to_sql(&fields_to_create);
+    let fields = sep_by(fields_to_create.into_iter(), ", ");
+    let unique_columns: Vec<Vec<String>> = table_attributes.unique_together.iter()
+        .map(|idents| idents.iter()
+            .filter_map(|ident| named.iter().find(|field| field.ident.as_ref() == Some(ident)))
+            .map(column_name)
+            .collect())
+        .collect();
+    let primary_key_columns =
+        if table_attributes.primary_key.is_empty() {
+            vec![]
+        }
+        else {
+            primary_key_columns(named, table_attributes)
+        };
+    let table_constraints = table_constraints_to_sql(&primary_key_columns, &unique_columns);
     let create_query = quote! {
-        concat!("CREATE TABLE ", #table, " (", #fields, ")")
+        concat!("CREATE TABLE ", #table, " (", #fields, #table_constraints #(, #foreign_keys)*, ")")
     };
     let macro_name = Ident::new(&format!("tql_{}_create_query", table_ident), Span::call_site());
+    // NOTE: the types of the enums are created before the table with some backends.
+    let create_types_macro_name = Ident::new(&format!("tql_{}_create_types", table_ident), Span::call_site());
+    let create_types = named.iter()
+        .filter(|field| embedded_struct(field).is_none())
+        .filter_map(|field| user_defined_type(&column_type(field, generics)).map(|typ| {
+            let macro_name = Ident::new(&format!("tql_{}_sql_type", typ), Span::call_site());
+            quote! { #macro_name!(create) }
+        }));
     quote! {
         #[macro_export]
         macro_rules! #macro_name {
             () => { #create_query };
         }
+
+        #[macro_export]
+        macro_rules! #create_types_macro_name {
+            () => { [#(#create_types),*] };
+        }
     }
 }
 
-fn related_pks_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+/// Create the macro giving the SQL condition to join the table related by a foreign key.
+fn join_condition_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident, table_name: &str) -> Tokens {
     let mut related_table_names = vec![];
-    let mut related_pk_macro_names = vec![];
+    let mut conditions = vec![];
     for field in named {
-        let typ = token_to_string(&field.ty);
         if let Some(ref ident) = field.ident {
-            if typ.starts_with("ForeignKey") {
-                if let syn::Type::Path(ref path) = field.ty {
-                    let element = path.path.segments.first().expect("first segment of path");
-                    let first_segment = element.value();
-                    if let Some(typ) = get_type_parameter(&first_segment.arguments) {
-                        related_table_names.push(ident);
-                        related_pk_macro_names.push(Ident::new(&format!("tql_{}_primary_key_field", typ),
-                            Span::call_site()));
+            if let Some(typ) = foreign_key_table(field) {
+                related_table_names.push(ident);
+                let alias = join_alias(ident, &typ);
+                let related_pk_macro_name = Ident::new(&format!("tql_{}_primary_key_field", typ),
+                    Span::call_site());
+                let foreign_key = field_attributes(&field.attrs).unwrap_or_default().foreign_key;
+                let condition =
+                    if foreign_key.is_empty() {
+                        let column = column_name(field);
+                        quote! {
+                            #table_name, ".", #column, " = ", #alias, ".", #related_pk_macro_name!(single)
+                        }
                     }
-                }
+                    else {
+                        // The foreign key is stored in multiple fields which are compared
+                        // to the columns of the primary key of the related table.
+                        let columns = foreign_key_columns(named, &foreign_key);
+                        let column_conditions = columns.iter()
+                            .enumerate()
+                            .map(|(index, column)| {
+                                let index = Literal::usize_unsuffixed(index);
+                                quote! {
+                                    #table_name, ".", #column, " = ", #alias, ".",
+                                        #related_pk_macro_name!(#index)
+                                }
+                            });
+                        sep_by(column_conditions, " AND ")
+                    };
+                conditions.push(condition);
+            }
+            else if let Type::ManyToMany(ref related_table) = field_ty_to_type(&field.ty).node {
+                // NOTE: the join goes through the link table. Both joins are left joins to keep the items
+                // without related items.
+                related_table_names.push(ident);
+                let pk_macro_name = Ident::new(&format!("tql_{}_primary_key_field", table_ident), Span::call_site());
+                let related_table_macro_name = Ident::new(&format!("tql_{}_table_name", related_table),
+                    Span::call_site());
+                let related_pk_macro_name = Ident::new(&format!("tql_{}_primary_key_field", related_table),
+                    Span::call_site());
+                let LinkTable { table, source, target } = link_table(field, table_ident, table_name, related_table);
+                let alias = join_alias(ident, related_table);
+                conditions.push(quote! {
+                    #table_name, ".", #pk_macro_name!(single), " = ", #table, ".", #source,
+                        " LEFT JOIN ", #related_table_macro_name!(), " AS ", #alias, " ON ", #table, ".", #target,
+                        " = ", #alias, ".", #related_pk_macro_name!(single)
+                });
             }
         }
     }
-    let macro_name = Ident::new(&format!("tql_{}_related_pks", table_ident), Span::call_site());
+    let macro_name = Ident::new(&format!("tql_{}_join_condition", table_ident), Span::call_site());
     quote! {
         #[macro_export]
         macro_rules! #macro_name {
-            #((#related_table_names) => { #related_pk_macro_names!() };)*
+            #((#related_table_names) => { concat!(#conditions) };)*
             // NOTE: the check for the field name is done elsewhere, hence it is okay to return
             // "" here.
             ($tt:tt) => { "" };
@@ -443,43 +1265,329 @@ fn related_pks_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> T
     }
 }
 
-fn pk_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
-    let macro_name = Ident::new(&format!("tql_{}_primary_key_field", table_ident), Span::call_site());
-    let mut primary_key = None;
-    for field in named {
-        let typ = token_to_string(&field.ty);
-        if let Some(ref ident) = field.ident {
-            if typ == "PrimaryKey" {
-                primary_key = Some(ident);
-            }
+/// Get the alias of the table joined by the `field`.
+/// NOTE: each join has its own alias since multiple fields can be related to the same table.
+fn join_alias(field: &Ident, related_table: &str) -> String {
+    format!("{}_{}", field, to_snake_case(related_table))
+}
+
+/// The SQL names of the link table of a `ManyToMany` field and of its columns referencing the
+/// primary keys of both tables.
+struct LinkTable {
+    table: Tokens,
+    source: Tokens,
+    target: Tokens,
+}
+
+/// Get the link table of the `ManyToMany` field.
+/// It is either the table of the model from `#[tql(through = "…")]` or a table named after the
+/// table and the field.
+fn link_table(field: &Field, table_ident: &Ident, table_name: &str, related_table: &str) -> LinkTable {
+    let related_table_ident = new_ident(related_table);
+    if let Some(through) = field_attributes(&field.attrs).unwrap_or_default().through {
+        let table_macro_name = Ident::new(&format!("tql_{}_table_name", through), Span::call_site());
+        let column_macro_name = Ident::new(&format!("tql_{}_foreign_key_column", through), Span::call_site());
+        // NOTE: for a relation to the same table, the second foreign key references the related item.
+        let target_index = Literal::usize_unsuffixed(if *table_ident == related_table_ident { 1 } else { 0 });
+        LinkTable {
+            table: quote! { #table_macro_name!() },
+            source: quote! { #column_macro_name!(#table_ident, 0) },
+            target: quote! { #column_macro_name!(#related_table_ident, #target_index) },
         }
     }
-    let primary_key =
-        if let Some(ident) = primary_key {
-            let ident = ident.to_string();
-            quote! {
-                #ident
-            }
+    else {
+        let table = format!("{}_{}", table_name, column_name(field));
+        let mut source = to_snake_case(&table_ident.to_string());
+        let mut target = to_snake_case(related_table);
+        if source == target {
+            source = format!("from_{}", source);
+            target = format!("to_{}", target);
         }
-        else {
-            quote! {
-                "-1" // FIXME: hack for when the table has no primary key.
+        LinkTable {
+            table: quote! { #table },
+            source: quote! { #source },
+            target: quote! { #target },
+        }
+    }
+}
+
+/// Create the macro giving the column of a foreign key from the related table and the index of
+/// the foreign key among those to this table.
+/// It is used when the model is the link table of a `ManyToMany` field.
+fn foreign_key_column_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let mut related_tables: Vec<String> = vec![];
+    let mut patterns = vec![];
+    for field in named {
+        if let Some(related_table) = foreign_key_table(field) {
+            if is_stored_in_other_fields(field) {
+                continue;
             }
-        };
+            let index = related_tables.iter()
+                .filter(|&table| *table == related_table)
+                .count();
+            let related_table_ident = new_ident(&related_table);
+            related_tables.push(related_table);
+            let index = Literal::usize_unsuffixed(index);
+            let column = column_name(field);
+            patterns.push(quote! {
+                (#related_table_ident, #index) => { #column };
+            });
+        }
+    }
+    let error = format!("the link table {} needs a ForeignKey to each table of the many-to-many relation",
+                        table_ident);
+    let macro_name = Ident::new(&format!("tql_{}_foreign_key_column", table_ident), Span::call_site());
     quote! {
         #[macro_export]
         macro_rules! #macro_name {
-            () => { #primary_key };
+            #(#patterns)*
+            ($table:ident, $index:tt) => { compile_error!(#error) };
         }
     }
 }
 
-fn check_missing_fields_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
-    let mut mandatory_fields = vec![];
+/// Create the macro giving the SQL code for the `ManyToMany` fields:
+/// the names of the link table and of its columns, the query to create or drop the link table and
+/// the column of a field of the related table.
+fn many_to_many_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident, table_name: &str) -> Tokens {
+    let mut patterns = vec![];
     for field in named {
-        let typ = token_to_string(&field.ty);
-        if let Some(ref ident) = field.ident {
-            if !typ.starts_with("Option") && typ != "PrimaryKey" {
+        let ident =
+            match field.ident {
+                Some(ref ident) => ident,
+                None => continue,
+            };
+        let related_table =
+            match field_ty_to_type(&field.ty).node {
+                Type::ManyToMany(related_table) => related_table,
+                typ => {
+                    let msg = string_literal(&format!("mismatched types
+expected type `ManyToMany<_>`
+   found type `{}`", typ));
+                    patterns.push(quote_spanned! { field.span() =>
+                        (#ident, $($tt:tt)*) => { compile_error!(#msg) };
+                    });
+                    continue;
+                },
+            };
+        let pk_macro_name = Ident::new(&format!("tql_{}_primary_key_field", table_ident), Span::call_site());
+        let related_table_macro_name = Ident::new(&format!("tql_{}_table_name", related_table), Span::call_site());
+        let related_pk_macro_name = Ident::new(&format!("tql_{}_primary_key_field", related_table),
+            Span::call_site());
+        let related_column_macro_name = Ident::new(&format!("tql_{}_column_name", related_table), Span::call_site());
+        let LinkTable { table, source, target } = link_table(field, table_ident, table_name, &related_table);
+        let alias = join_alias(ident, &related_table);
+        // NOTE: the rows of a through model have other columns, so they are only modified with this model.
+        let (create_query, drop_query, modified_table) =
+            if field_attributes(&field.attrs).unwrap_or_default().through.is_some() {
+                let error = format!("the link table of the many-to-many field {} is the table of its through model: use this model instead",
+                                    ident);
+                let error = quote_spanned! { ident.span() =>
+                    compile_error!(#error)
+                };
+                (error.clone(), error.clone(), error)
+            }
+            else {
+                (quote! {
+                    concat!("CREATE TABLE ", #table, " (",
+                            #source, " ", #pk_macro_name!(sql_type), " REFERENCES ", #table_name, "(",
+                            #pk_macro_name!(single), ") NOT NULL, ",
+                            #target, " ", #related_pk_macro_name!(sql_type), " REFERENCES ",
+                            #related_table_macro_name!(), "(", #related_pk_macro_name!(single), ") NOT NULL, ",
+                            "PRIMARY KEY (", #source, ", ", #target, "))")
+                },
+                quote! {
+                    concat!("DROP TABLE ", #table)
+                },
+                quote! { #table })
+            };
+        patterns.push(quote! {
+            (#ident, create) => { #create_query };
+            (#ident, drop) => { #drop_query };
+            (#ident, modified_table) => { #modified_table };
+            (#ident, related_alias) => { #alias };
+            (#ident, related_column, $field:ident) => { #related_column_macro_name!($field) };
+            (#ident, related_primary_key) => { #related_pk_macro_name!(single) };
+            (#ident, related_table) => { #related_table_macro_name!() };
+            (#ident, source) => { #source };
+            (#ident, table) => { #table };
+            (#ident, target) => { #target };
+        });
+    }
+    let macro_name = Ident::new(&format!("tql_{}_many_to_many", table_ident), Span::call_site());
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            #(#patterns)*
+            // NOTE: the check for the field name is done elsewhere, hence it is okay to return
+            // "" here.
+            ($($tt:tt)*) => { "" };
+        }
+    }
+}
+
+/// Create the methods of the tables related by the `ForeignKey` fields which get the rows of this
+/// table referencing an item, like `author.books(&connection)` for `author: ForeignKey<Author>` in
+/// `Book`.
+fn reverse_relations_impl(named: &Punctuated<Field, Comma>, table_ident: &Ident, generics: &Generics,
+                          table_name: &str, table_attributes: &TableAttributes) -> Tokens
+{
+    let backend = create_backend();
+    // NOTE: the rows deleted with soft_delete are excluded.
+    let fields: Vec<_> = named.iter().cloned().collect();
+    let not_deleted = soft_delete_field(&fields, table_attributes)
+        .map(|field| format!(" AND {}.{} IS NULL", table_name, column_name(field)))
+        .unwrap_or_default();
+    // NOTE: the generic parameters of the table are the generic parameters of the methods.
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let table_type = quote! { #table_ident #ty_generics };
+    let related_tables: Vec<_> = named.iter()
+        .filter(|field| field_attributes(&field.attrs).unwrap_or_default().related_name.is_none() &&
+                !is_stored_in_other_fields(field))
+        .filter_map(foreign_key_table)
+        .collect();
+    let default_name = pluralize(&to_snake_case(&table_ident.to_string()));
+    let mut impls = vec![];
+    for field in named {
+        let (related_table, related_type) =
+            match (foreign_key_table(field), foreign_key_path(&field.ty)) {
+                (Some(related_table), Some(related_type)) => (related_table, related_type),
+                _ => continue,
+            };
+        // NOTE: a foreign key stored in multiple fields cannot be compared to the related item, and
+        // the method cannot be added to a related table using the type parameters of this table.
+        if is_stored_in_other_fields(field) || uses_type_parameters(related_type, generics) {
+            continue;
+        }
+        let field_ident = field.ident.as_ref().expect("field has name");
+        // NOTE: the default methods of the fields related to the same table are prefixed by the
+        // field name.
+        let method_name =
+            match field_attributes(&field.attrs).unwrap_or_default().related_name {
+                Some(related_name) => related_name,
+                None if related_tables.iter().filter(|&table| *table == related_table).count() > 1 =>
+                    new_ident(&format!("{}_{}", field_ident, default_name)),
+                None => new_ident(&default_name),
+            };
+        let columns = field_list(named, quote! { #table_name });
+        let condition = format!(" FROM {} WHERE {}.{} = $1{}", table_name, table_name, column_name(field), not_deleted);
+        let sql_query = quote! {
+            concat!("SELECT ", #columns, #condition)
+        };
+        let query = backend.select_multi(quote! { connection }, &sql_query, quote! { [self] },
+                                         create_struct(table_ident, &table_type, &[]));
+        let connection_type = backend.connection_type();
+        let result_type = backend.result_type(quote! { Vec<#table_type> });
+        impls.push(quote_spanned! { field.span() =>
+            impl #related_type {
+                #[allow(dead_code)]
+                pub fn #method_name #impl_generics (&self, connection: &#connection_type) -> #result_type
+                    #where_clause
+                {
+                    #query
+                }
+            }
+        });
+    }
+    quote! {
+        #(#impls)*
+    }
+}
+
+/// Get the columns of the fields from a `#[tql(foreign_key(…))]` attribute.
+fn foreign_key_columns(named: &Punctuated<Field, Comma>, foreign_key: &[Ident]) -> Vec<String> {
+    foreign_key.iter()
+        .filter_map(|ident| named.iter().find(|field| field.ident.as_ref() == Some(ident)))
+        .map(column_name)
+        .collect()
+}
+
+/// Get the fields of the primary key, either from the `#[tql(primary_key(…))]` attribute or the
+/// `PrimaryKey` field.
+fn primary_key_fields<'a>(named: &'a Punctuated<Field, Comma>, table_attributes: &TableAttributes) -> Vec<&'a Field> {
+    if !table_attributes.primary_key.is_empty() {
+        return table_attributes.primary_key.iter()
+            .filter_map(|ident| named.iter().find(|field| field.ident.as_ref() == Some(ident)))
+            .collect();
+    }
+    named.iter()
+        .filter(|field| is_primary_key(field))
+        .take(1)
+        .collect()
+}
+
+/// Get the columns of the primary key.
+fn primary_key_columns(named: &Punctuated<Field, Comma>, table_attributes: &TableAttributes) -> Vec<String> {
+    primary_key_fields(named, table_attributes).into_iter()
+        .map(column_name)
+        .collect()
+}
+
+/// Create the macro giving the columns of the primary key:
+/// all of them with `()`, a single one with `(single)` and the column at an index with `(index)`.
+/// The SQL type of a single primary key, used by the foreign keys, is given by `(sql_type)`.
+fn pk_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident, table_attributes: &TableAttributes) -> Tokens {
+    let macro_name = Ident::new(&format!("tql_{}_primary_key_field", table_ident), Span::call_site());
+    let fields = primary_key_fields(named, table_attributes);
+    let columns = primary_key_columns(named, table_attributes);
+    let primary_key =
+        if columns.is_empty() {
+            quote! {
+                "-1" // FIXME: hack for when the table has no primary key.
+            }
+        }
+        else {
+            let columns = columns.join(", ");
+            quote! {
+                #columns
+            }
+        };
+    let single_primary_key =
+        if columns.len() > 1 {
+            let error = format!("the primary key of table {} has multiple columns ({})", table_ident,
+                                columns.join(", "));
+            quote_spanned! { table_ident.span() =>
+                compile_error!(#error)
+            }
+        }
+        else {
+            primary_key.clone()
+        };
+    let sql_type =
+        match fields.first() {
+            Some(field) if fields.len() == 1 => {
+                // NOTE: the column of a foreign key is not generated like its primary key.
+                let typ =
+                    match field_ty_to_type(&field.ty).node {
+                        Type::PrimaryKey(typ) => *typ,
+                        typ => typ,
+                    };
+                sql::type_to_sql(&typ, true)
+            },
+            Some(_) => single_primary_key.clone(),
+            None => quote! { "INTEGER" },
+        };
+    let indexes = (0..columns.len()).map(Literal::usize_unsuffixed);
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            () => { #primary_key };
+            (single) => { #single_primary_key };
+            (sql_type) => { #sql_type };
+            #((#indexes) => { #columns };)*
+        }
+    }
+}
+
+fn check_missing_fields_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let mut mandatory_fields = vec![];
+    for field in named {
+        let typ = token_to_string(&field.ty);
+        if let Some(ref ident) = field.ident {
+            // NOTE: a foreign key stored in other fields is assigned through these fields.
+            if !typ.starts_with("Option") && !is_generated_primary_key(field) && !is_stored_in_other_fields(field) &&
+                !is_many_to_many(field) && !is_auto_now(field)
+            {
                 mandatory_fields.push(ident);
             }
         }
@@ -503,31 +1611,334 @@ fn check_missing_fields_macro(named: &Punctuated<Field, Comma>, table_ident: &Id
     }
 }
 
-fn related_table_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+/// Create the macro adding the primary key generated by tql to an insert query, unless it is one of the inserted
+/// fields: its column with `(column [fields])`, its parameter with `(value "$n" [fields])` and its value to the
+/// arguments with `(arguments [fields] [arguments])`.
+fn generated_primary_key_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident,
+                               table_attributes: &TableAttributes) -> Tokens
+{
+    let macro_name = Ident::new(&format!("tql_{}_generated_primary_key", table_ident), Span::call_site());
+    let fields = primary_key_fields(named, table_attributes);
+    let uuid_field = fields.first()
+        .filter(|field| fields.len() == 1 && field_ty_to_type(&field.ty).node == Type::PrimaryKey(Box::new(Type::Uuid)));
+    if let Some(field) = uuid_field {
+        let ident = field.ident.as_ref().expect("field ident");
+        let column = format!(", {}", column_name(field));
+        quote! {
+            #[macro_export]
+            macro_rules! #macro_name {
+                (column [$($fields:ident),*]) => { #macro_name!(@find [$($fields),*] "" #column) };
+                (value $value:tt [$($fields:ident),*]) => { #macro_name!(@find [$($fields),*] "" $value) };
+                (arguments [$($fields:ident),*] [$($arguments:expr),*]) => {
+                    #macro_name!(@find [$($fields),*] [$($arguments),*] [$($arguments,)* &::tql::generate_uuid()])
+                };
+                (@find [] $inserted:tt $generated:tt) => { $generated };
+                (@find [#ident $(, $fields:ident)*] $inserted:tt $generated:tt) => { $inserted };
+                (@find [$field:ident $(, $fields:ident)*] $inserted:tt $generated:tt) => {
+                    #macro_name!(@find [$($fields),*] $inserted $generated)
+                };
+            }
+        }
+    }
+    else {
+        quote! {
+            #[macro_export]
+            macro_rules! #macro_name {
+                (column [$($fields:ident),*]) => { "" };
+                (value $value:tt [$($fields:ident),*]) => { "" };
+                (arguments [$($fields:ident),*] [$($arguments:expr),*]) => { [$($arguments),*] };
+            }
+        }
+    }
+}
+
+/// Create the macro adding the current time to the fields with `#[tql(auto_now)]` or `#[tql(auto_now_add)]`,
+/// unless they are assigned by the query: their columns with `(column [fields])` and their values with
+/// `(value [fields])` in an insert query, and the assignments of the `auto_now` fields with `(update [fields])`.
+fn auto_now_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let macro_name = Ident::new(&format!("tql_{}_auto_now", table_ident), Span::call_site());
+    let mut columns = vec![];
+    let mut values = vec![];
+    let mut updates = vec![];
+    let mut find_patterns = vec![];
+    for field in named.iter().filter(|field| is_auto_now(field)) {
+        let ident = field.ident.as_ref().expect("field ident");
+        let column = column_name(field);
+        let current_timestamp =
+            match current_timestamp(&field_ty_to_type(&field.ty).node) {
+                Some(current_timestamp) => current_timestamp,
+                None => continue, // NOTE: the error is reported by get_struct_fields().
+            };
+        let column_sql = format!(", {}", column);
+        let value_sql = format!(", {}", current_timestamp);
+        columns.push(quote! { #macro_name!(@find #ident [$($fields),*] "" #column_sql) });
+        values.push(quote! { #macro_name!(@find #ident [$($fields),*] "" #value_sql) });
+        if field_attributes(&field.attrs).unwrap_or_default().auto_now {
+            let update_sql = format!(", {} = {}", column, current_timestamp);
+            updates.push(quote! { #macro_name!(@find #ident [$($fields),*] "" #update_sql) });
+        }
+        find_patterns.push(quote! {
+            (@find #ident [#ident $(, $fields:ident)*] $assigned:tt $current:tt) => { $assigned };
+        });
+    }
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            (column [$($fields:ident),*]) => { concat!(#(#columns),*) };
+            (value [$($fields:ident),*]) => { concat!(#(#values),*) };
+            (update [$($fields:ident),*]) => { concat!(#(#updates),*) };
+            #(#find_patterns)*
+            (@find $field:ident [] $assigned:tt $current:tt) => { $current };
+            (@find $field:ident [$other:ident $(, $fields:ident)*] $assigned:tt $current:tt) => {
+                #macro_name!(@find $field [$($fields),*] $assigned $current)
+            };
+        }
+    }
+}
+
+/// Create the macro checking the value assigned to a field by an insert or an update with `(field, value)`,
+/// which gives a `ValidationError` when it is longer than `#[tql(max_length = …)]` or when the function from
+/// `#[tql(validate = "…")]` fails.
+fn validate_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let macro_name = Ident::new(&format!("tql_{}_validate", table_ident), Span::call_site());
+    let patterns = named.iter()
+        .filter_map(|field| {
+            let attributes = field_attributes(&field.attrs).ok()?;
+            let ident = field.ident.as_ref()?;
+            let name = ident.to_string();
+            let mut checks = vec![];
+            // NOTE: the error is reported by get_struct_fields() when the field is not a String.
+            if let Some(max_length) = attributes.max_length.filter(|_| is_string(field)) {
+                let max_length = max_length as usize;
+                checks.push(quote! { ::tql::check_max_length(&value, #max_length) });
+            }
+            if let Some(validate) = attributes.validate {
+                checks.push(quote! { #validate(&value) });
+            }
+            if checks.is_empty() {
+                return None;
+            }
+            let checks = checks.iter().map(|check| quote! {
+                .and_then(|()| #check.map_err(|message| ::tql::ValidationError::new(#name, message)))
+            });
+            Some(quote! {
+                (#ident, $value:expr) => {{
+                    let value = $value.to_owned();
+                    ::std::result::Result::Ok(()) #(#checks)*
+                }};
+            })
+        });
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            #(#patterns)*
+            ($field:ident, $value:expr) => { ::std::result::Result::Ok::<(), ::tql::ValidationError>(()) };
+        }
+    }
+}
+
+/// Create the macro checking at compile time the length of a string literal assigned by an insert or an
+/// update with `(field, literal)`, which gives an error when it is longer than `#[tql(max_length = …)]`.
+fn check_max_length_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let macro_name = Ident::new(&format!("tql_{}_check_max_length", table_ident), Span::call_site());
+    #[cfg(feature = "unstable")]
+    let macro_call = quote_spanned! { table_ident.span() =>
+        ::tql_macros::check_max_length!
+    };
+    #[cfg(not(feature = "unstable"))]
+    let macro_call = quote! {
+        check_max_length!
+    };
+    let patterns = named.iter()
+        .filter(|field| is_string(field))
+        .filter_map(|field| {
+            let max_length = field_attributes(&field.attrs).ok()?.max_length?;
+            let ident = field.ident.as_ref()?;
+            Some(quote! {
+                (#ident, $literal:expr) => {
+                    #macro_call(#ident, #max_length, $literal)
+                };
+            })
+        });
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            #(#patterns)*
+            ($field:ident, $literal:expr) => {};
+        }
+    }
+}
+
+/// Create the macro giving the SQL code selecting the rows of a table with `#[tql(soft_delete)]`: the
+/// statement deleting them with `(delete)` and `(hard_delete)`, and the where clause with
+/// `(filter_start deleted)` and `(filter_end deleted)` around a filter or with `(no_filter deleted)`, where
+/// `deleted` is `exclude`, `include` or `only`.
+fn soft_delete_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident, table_name: &str,
+                     table_attributes: &TableAttributes) -> Tokens
+{
+    let macro_name = Ident::new(&format!("tql_{}_soft_delete", table_ident), Span::call_site());
+    let fields: Vec<_> = named.iter().cloned().collect();
+    let delete = format!("DELETE FROM {}", table_name);
+    if let Some(field) = soft_delete_field(&fields, table_attributes) {
+        let column = column_name(field);
+        let update = format!("UPDATE {} SET {} = {}", table_name, column,
+                             current_timestamp(&Type::UtcDateTime).expect("current timestamp"));
+        let not_deleted = format!("{}.{} IS NULL", table_name, column);
+        let deleted = format!("{}.{} IS NOT NULL", table_name, column);
+        let exclude_start = format!(" WHERE {} AND (", not_deleted);
+        let only_start = format!(" WHERE {} AND (", deleted);
+        let exclude = format!(" WHERE {}", not_deleted);
+        let only = format!(" WHERE {}", deleted);
+        quote! {
+            #[macro_export]
+            macro_rules! #macro_name {
+                (delete) => { #update };
+                (hard_delete) => { #delete };
+                (filter_start include) => { " WHERE " };
+                (filter_start exclude) => { #exclude_start };
+                (filter_start only) => { #only_start };
+                (filter_end include) => { "" };
+                (filter_end $deleted:ident) => { ")" };
+                (no_filter include) => { "" };
+                (no_filter exclude) => { #exclude };
+                (no_filter only) => { #only };
+            }
+        }
+    }
+    else {
+        let error = format!("the table `{}` does not use #[tql(soft_delete)]", table_ident);
+        quote! {
+            #[macro_export]
+            macro_rules! #macro_name {
+                (delete) => { #delete };
+                (hard_delete) => { compile_error!(#error) };
+                (filter_start exclude) => { " WHERE " };
+                (filter_start $deleted:ident) => { compile_error!(#error) };
+                (filter_end $deleted:ident) => { "" };
+                (no_filter exclude) => { "" };
+                (no_filter $deleted:ident) => { compile_error!(#error) };
+            }
+        }
+    }
+}
+
+/// Create the macro converting an argument given to a field with `(field, argument)`: the value of a
+/// field not converted by the database library (like an unsigned integer) is sent in a wrapper type.
+/// The argument given to a field of an embedded struct is converted with `(field.embedded_field, argument)`.
+fn argument_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident, generics: &Generics) -> Tokens {
+    let macro_name = Ident::new(&format!("tql_{}_argument", table_ident), Span::call_site());
+    let converted_fields = named.iter()
+        .filter(|field| is_converted(&field.ty))
+        .map(|field| &field.ident);
+    // NOTE: a field whose type is a type parameter is sent as the representation of its SqlType.
+    let generic_fields = named.iter()
+        .filter(|field| type_parameter(&field.ty, generics).is_some())
+        .map(|field| &field.ident);
+    let embedded_patterns = named.iter()
+        .filter_map(|field| embedded_struct(field).map(|typ| {
+            let ident = &field.ident;
+            let embedded_macro_name = Ident::new(&format!("tql_{}_embedded", typ), Span::call_site());
+            quote! {
+                (#ident.$field:ident, $argument:expr) => { #embedded_macro_name!(argument $field, $argument) };
+            }
+        }));
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            #((#converted_fields, $argument:expr) => { &::tql::Convert::to_wrapper($argument) };)*
+            #((#generic_fields, $argument:expr) => { &::tql::SqlType::to_repr($argument) };)*
+            ($field:ident, $argument:expr) => { $argument };
+            #(#embedded_patterns)*
+            ($relation:ident.$field:ident, $argument:expr) => { $argument };
+        }
+    }
+}
+
+/// Create the macro for the fields accessed through another field, which is either embedded or a `ManyToMany`: the
+/// condition on such a field with `(relation.field [many-to-many tokens])`, the end of this condition with
+/// `(relation end)` and the item containing the field in the table struct with `(item table relation)`.
+fn embedded_field_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident, table_name: &str) -> Tokens {
+    let macro_name = Ident::new(&format!("tql_{}_embedded_field", table_ident), Span::call_site());
+    let column_macro_name = Ident::new(&format!("tql_{}_column_name", table_ident), Span::call_site());
+    let embedded_patterns = named.iter()
+        .filter(|field| embedded_struct(field).is_some())
+        .map(|field| {
+            let ident = &field.ident;
+            quote! {
+                (#ident.$field:ident [$($related:tt)*]) => {
+                    concat!(#table_name, ".", #column_macro_name!(#ident.$field))
+                };
+                (#ident end) => { "" };
+                (item $table:ident #ident) => { $table.#ident };
+            }
+        });
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            #(#embedded_patterns)*
+            // NOTE: the condition on a field of the items related by a ManyToMany field is in a subquery.
+            ($relation:ident.$field:ident [$($related:tt)*]) => { concat!($($related)*) };
+            ($relation:ident end) => { ")" };
+            (item $table:ident $relation:ident) => { $table.$relation[0] };
+        }
+    }
+}
+
+/// Create the macro choosing the SQL of a method existing on both the arrays and another type with
+/// `(field [array tokens] [other tokens])`.
+fn array_field_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let macro_name = Ident::new(&format!("tql_{}_array_field", table_ident), Span::call_site());
+    let array_fields = named.iter()
+        .filter(|field| matches!(field_ty_to_type(&field.ty).node, Type::Array(_)))
+        .map(|field| &field.ident);
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            #((#array_fields [$($array:tt)*] [$($other:tt)*]) => { concat!($($array)*) };)*
+            ($field:ident [$($array:tt)*] [$($other:tt)*]) => { concat!($($other)*) };
+        }
+    }
+}
+
+fn related_table_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident, table_name: &str) -> Tokens {
     let mut related_table_names = vec![];
     let mut non_related_table_names = vec![];
     let mut related_tables = vec![];
     let mut check_related_pk = vec![];
     let mut compiler_errors = vec![];
+    let mut nullable_related_table_names = vec![];
     for field in named {
         let typ = token_to_string(&field.ty);
         if let Some(ref ident) = field.ident {
-            if typ.starts_with("ForeignKey") {
-                if let syn::Type::Path(ref path) = field.ty {
-                    let element = path.path.segments.first().expect("first segment of path");
-                    let first_segment = element.value();
-                    if let Some(typ) = get_type_parameter(&first_segment.arguments) {
-                        related_table_names.push(ident);
-                        let span = get_type_parameter_as_path(&first_segment.arguments)
-                            .expect("ForeignKey inner type")
-                            .span();
-                        let macro_name = Ident::new(&format!("tql_{}_check_primary_key", typ), span);
-                        check_related_pk.push(quote_spanned! { span =>
-                            #macro_name!();
-                        });
-                        related_tables.push(typ);
-                    }
+            if let Some(typ) = foreign_key_table(field) {
+                related_table_names.push(ident);
+                if is_nullable_foreign_key(field) {
+                    nullable_related_table_names.push(ident);
                 }
+                let span = foreign_key_path(&field.ty)
+                    .expect("ForeignKey inner type")
+                    .span();
+                let macro_name = Ident::new(&format!("tql_{}_check_primary_key", typ), span);
+                check_related_pk.push(quote_spanned! { span =>
+                    #macro_name!();
+                });
+                let table_name_macro = Ident::new(&format!("tql_{}_table_name", typ), Span::call_site());
+                let alias = join_alias(ident, &typ);
+                related_tables.push(quote! {
+                    concat!(#table_name_macro!(), " AS ", #alias)
+                });
+            }
+            else if let Type::ManyToMany(ref typ) = field_ty_to_type(&field.ty).node {
+                related_table_names.push(ident);
+                nullable_related_table_names.push(ident);
+                let span = field.ty.span();
+                let macro_name = Ident::new(&format!("tql_{}_check_primary_key", typ), span);
+                check_related_pk.push(quote_spanned! { span =>
+                    #macro_name!();
+                });
+                // NOTE: the join condition joins the related table after the link table.
+                let LinkTable { table, .. } = link_table(field, table_ident, table_name, typ);
+                related_tables.push(table);
             }
             else {
                 non_related_table_names.push(ident);
@@ -545,6 +1956,7 @@ expected type `ForeignKey<_>`
     let macro_name = Ident::new(&format!("tql_{}_related_tables", table_ident), Span::call_site());
     let check_macro_name = Ident::new(&format!("tql_{}_check_related_tables", table_ident), Span::call_site());
     let check_related_pk_macro_name = Ident::new(&format!("tql_{}_check_related_pks", table_ident), Span::call_site());
+    let join_type_macro_name = Ident::new(&format!("tql_{}_join_type", table_ident), Span::call_site());
     quote! {
         #[macro_export]
         macro_rules! #macro_name {
@@ -565,16 +1977,21 @@ expected type `ForeignKey<_>`
             #((#related_table_names2) => { #check_related_pk };)*
             ($tt:tt) => {};
         }
+
+        // NOTE: a nullable foreign key and a many-to-many field use a left join to get the rows
+        // without a related row.
+        #[macro_export]
+        macro_rules! #join_type_macro_name {
+            #((#nullable_related_table_names) => { " LEFT JOIN " };)*
+            ($tt:tt) => { " INNER JOIN " };
+        }
     }
 }
 
-fn check_pk_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
-    let mut primary_key_found = false;
-    for field in named {
-        let typ = token_to_string(&field.ty);
-        if typ == "PrimaryKey" {
-            primary_key_found = true;
-        }
+fn check_pk_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident, table_attributes: &TableAttributes) -> Tokens {
+    let mut primary_key_found = !table_attributes.primary_key.is_empty();
+    if named.iter().any(is_primary_key) {
+        primary_key_found = true;
     }
     let macro_name = Ident::new(&format!("tql_{}_check_primary_key", table_ident), Span::call_site());
     let pk_code =
@@ -598,6 +2015,69 @@ fn check_pk_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Toke
     }
 }
 
+/// Create the macros giving the SQL names of the table and of its columns.
+/// The column of a field of an embedded struct is given by `(field.embedded_field)`.
+fn names_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident, table_name: &str) -> Tokens {
+    let mut field_idents = vec![];
+    let mut columns = vec![];
+    let mut embedded_patterns = vec![];
+    for field in named {
+        if let Some(ref ident) = field.ident {
+            field_idents.push(ident);
+            let foreign_key = field_attributes(&field.attrs).unwrap_or_default().foreign_key;
+            if let Some(typ) = embedded_struct(field) {
+                let error = format!("the embedded field {} is stored in the columns of its fields: use these fields instead, like {}.field",
+                                    ident, ident);
+                columns.push(quote_spanned! { ident.span() =>
+                    compile_error!(#error)
+                });
+                let macro_name = Ident::new(&format!("tql_{}_embedded", typ), Span::call_site());
+                let prefix = format!("{}_", column_name(field));
+                embedded_patterns.push(quote! {
+                    (#ident.$field:ident) => { #macro_name!(column #prefix, $field) };
+                });
+            }
+            else if is_many_to_many(field) {
+                let error = format!("the many-to-many field {} is not a column: use its methods add(), clear() or remove() instead",
+                                    ident);
+                columns.push(quote_spanned! { ident.span() =>
+                    compile_error!(#error)
+                });
+            }
+            else if foreign_key.is_empty() {
+                let column = column_name(field);
+                columns.push(quote! { #column });
+            }
+            else {
+                let fields: Vec<_> = foreign_key.iter().map(Ident::to_string).collect();
+                let error = format!("the foreign key {} is stored in the fields {}: use these fields instead",
+                                    ident, fields.join(", "));
+                columns.push(quote_spanned! { ident.span() =>
+                    compile_error!(#error)
+                });
+            }
+        }
+    }
+    let table_macro_name = Ident::new(&format!("tql_{}_table_name", table_ident), Span::call_site());
+    let column_macro_name = Ident::new(&format!("tql_{}_column_name", table_ident), Span::call_site());
+    quote! {
+        #[macro_export]
+        macro_rules! #table_macro_name {
+            () => { #table_name };
+        }
+
+        #[macro_export]
+        macro_rules! #column_macro_name {
+            #((#field_idents) => { #columns };)*
+            #(#embedded_patterns)*
+            // NOTE: the check for the field name is done elsewhere, hence it is okay to return
+            // "" here.
+            ($tt:tt) => { "" };
+            ($field:ident.$embedded_field:ident) => { "" };
+        }
+    }
+}
+
 /// Create the insert macro for the table struct to check that all the mandatory fields are
 /// provided.
 pub fn table_macro(item_struct: &ItemStruct) -> Tokens {
@@ -608,32 +2088,51 @@ pub fn table_macro(item_struct: &ItemStruct) -> Tokens {
         for field in named {
             let typ = token_to_string(&field.ty);
             if let Some(ref ident) = field.ident {
-                if !typ.starts_with("Option") && typ != "PrimaryKey" {
+                if !typ.starts_with("Option") && !is_generated_primary_key(field) && !is_many_to_many(field) {
                     mandatory_fields.push(ident);
                 }
-                if typ.starts_with("ForeignKey") {
-                    if let syn::Type::Path(ref path) = field.ty {
-                        let element = path.path.segments.first().expect("first segment of path");
-                        let first_segment = element.value();
-                        if let Some(typ) = get_type_parameter(&first_segment.arguments) {
-                            let macro_name = Ident::new(&format!("tql_{}_field_list", typ), Span::call_site());
-                            fk_patterns.push(quote_spanned! { table_ident.span() =>
-                                (#ident) => { #macro_name!() };
-                            });
-                        }
-                    }
+                if let Some(typ) = foreign_key_table(field) {
+                    let macro_name = Ident::new(&format!("tql_{}_field_list", typ), Span::call_site());
+                    let alias = join_alias(ident, &typ);
+                    fk_patterns.push(quote_spanned! { table_ident.span() =>
+                        (#ident) => { #macro_name!(#alias) };
+                    });
+                }
+                if let Type::ManyToMany(ref typ) = field_ty_to_type(&field.ty).node {
+                    let macro_name = Ident::new(&format!("tql_{}_field_list", typ), Span::call_site());
+                    let alias = join_alias(ident, typ);
+                    fk_patterns.push(quote_spanned! { table_ident.span() =>
+                        (#ident) => { #macro_name!(#alias) };
+                    });
                 }
             }
         }
 
+        let table_name = table_name(item_struct);
         let related_field_list_macro_name = Ident::new(&format!("tql_{}_related_field_list", table_ident), Span::call_site());
+        let names_macro = names_macro(named, table_ident, &table_name);
         let check_missing_fields_macro = check_missing_fields_macro(named, table_ident);
-        let field_list_macro = field_list_macro(named, table_ident);
-        let create_query_macro = create_query_macro(named, table_ident);
-        let pk_macro = pk_macro(named, table_ident);
-        let related_pks_macro = related_pks_macro(named, table_ident);
-        let related_table_macro = related_table_macro(named, table_ident);
-        let check_pk_macro = check_pk_macro(named, table_ident);
+        let field_list_macro = field_list_macro(named, table_ident, &table_name);
+        let table_attributes = table_attributes(&item_struct.attrs).unwrap_or_default();
+        let generated_primary_key_macro = generated_primary_key_macro(named, table_ident, &table_attributes);
+        let auto_now_macro = auto_now_macro(named, table_ident);
+        let soft_delete_macro = soft_delete_macro(named, table_ident, &table_name, &table_attributes);
+        let validate_macro = validate_macro(named, table_ident);
+        let check_max_length_macro = check_max_length_macro(named, table_ident);
+        let argument_macro = argument_macro(named, table_ident, &item_struct.generics);
+        let embedded_field_macro = embedded_field_macro(named, table_ident, &table_name);
+        let array_field_macro = array_field_macro(named, table_ident);
+        let create_query_macro = create_query_macro(named, table_ident, &item_struct.generics, &table_name,
+                                                    &table_attributes);
+        let pk_macro = pk_macro(named, table_ident, &table_attributes);
+        let join_condition_macro = join_condition_macro(named, table_ident, &table_name);
+        let related_table_macro = related_table_macro(named, table_ident, &table_name);
+        let check_pk_macro = check_pk_macro(named, table_ident, &table_attributes);
+        let foreign_key_column_macro = foreign_key_column_macro(named, table_ident);
+        let many_to_many_macro = many_to_many_macro(named, table_ident, &table_name);
+        let reverse_relations_impl = reverse_relations_impl(named, table_ident, &item_struct.generics, &table_name,
+                                                            &table_attributes);
+        let type_macro = type_macro(table_ident, &item_struct.generics);
         quote! {
             #[macro_export]
             macro_rules! #related_field_list_macro_name {
@@ -641,13 +2140,26 @@ pub fn table_macro(item_struct: &ItemStruct) -> Tokens {
                 ($tt:tt) => { "" };
             }
 
+            #type_macro
+            #names_macro
             #check_pk_macro
             #related_table_macro
             #check_missing_fields_macro
+            #generated_primary_key_macro
+            #auto_now_macro
+            #soft_delete_macro
+            #validate_macro
+            #check_max_length_macro
+            #argument_macro
+            #embedded_field_macro
+            #array_field_macro
             #field_list_macro
             #create_query_macro
-            #related_pks_macro
+            #join_condition_macro
             #pk_macro
+            #foreign_key_column_macro
+            #many_to_many_macro
+            #reverse_relations_impl
         }
     }
     else {
@@ -655,20 +2167,297 @@ pub fn table_macro(item_struct: &ItemStruct) -> Tokens {
     }
 }
 
-fn to_row_get(typ: syn::Type, with_delta: bool, index: &mut usize) -> Tokens {
-    if let syn::Type::Path(path) = typ {
-        let segment = path.path.segments.first().expect("first segment").into_value();
+/// Create the macro giving the type of the table used in the queries, whose type arguments are inferred.
+fn type_macro(table_ident: &Ident, generics: &Generics) -> Tokens {
+    let macro_name = Ident::new(&format!("tql_{}_type", table_ident), Span::call_site());
+    let arguments: Vec<_> = generics.type_params()
+        .map(|_| quote! { _ })
+        .collect();
+    // NOTE: the lifetimes can be elided in the queries.
+    let typ =
+        if arguments.is_empty() {
+            quote! { #table_ident }
+        }
+        else {
+            quote! { #table_ident<#(#arguments),*> }
+        };
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            () => { #typ };
+        }
+    }
+}
+
+/// Get the name of the type of a field implementing the `SqlType` trait, which can be nullable or a
+/// primary key.
+fn user_defined_type(typ: &Type) -> Option<&str> {
+    match *typ {
+        Type::Nullable(ref typ) | Type::PrimaryKey(ref typ) => user_defined_type(typ),
+        Type::UserDefined(ref typ) => Some(typ),
+        _ => None,
+    }
+}
+
+/// Get the type parameter of the struct used as the type of a field, like `T` in `value: T`.
+fn type_parameter<'a>(typ: &syn::Type, generics: &'a Generics) -> Option<&'a TypeParam> {
+    if let syn::Type::Path(TypePath { qself: None, ref path }) = *typ {
+        return generics.type_params()
+            .find(|param| path.is_ident(param.ident.clone()));
+    }
+    None
+}
+
+/// Get the representation of a type parameter from its `SqlType<Repr = …>` bound, which is either in the
+/// parameter list or in the where clause.
+fn type_parameter_repr<'a>(param: &'a TypeParam, generics: &'a Generics) -> Option<&'a syn::Type> {
+    let where_bounds = generics.where_clause.iter()
+        .flat_map(|where_clause| where_clause.predicates.iter())
+        .filter_map(|predicate| match *predicate {
+            WherePredicate::Type(PredicateType { bounded_ty: syn::Type::Path(TypePath { qself: None, ref path }),
+                ref bounds, .. }) if path.is_ident(param.ident.clone()) => Some(bounds.iter()),
+            _ => None,
+        })
+        .flatten();
+    param.bounds.iter()
+        .chain(where_bounds)
+        .filter_map(|bound| match *bound {
+            TypeParamBound::Trait(ref bound) => bound.path.segments.last().map(Pair::into_value),
+            _ => None,
+        })
+        .filter(|segment| segment.ident == "SqlType")
+        .filter_map(|segment| match segment.arguments {
+            AngleBracketed(AngleBracketedGenericArguments { ref args, .. }) => Some(args.iter()),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|argument| match *argument {
+            GenericArgument::Binding(ref binding) if binding.ident == "Repr" => Some(&binding.ty),
+            _ => None,
+        })
+        .next()
+}
+
+/// Get the type of the column of a field, which is the representation of its type when it is a type
+/// parameter of the struct.
+fn column_type(field: &Field, generics: &Generics) -> Type {
+    let repr = type_parameter(&field.ty, generics)
+        .and_then(|param| type_parameter_repr(param, generics));
+    field_ty_to_type(repr.unwrap_or(&field.ty)).node
+}
+
+/// Check if the type uses one of the type parameters of the struct.
+fn uses_type_parameters<T: ToTokens>(typ: &T, generics: &Generics) -> bool {
+    fn uses(tokens: Tokens, generics: &Generics) -> bool {
+        tokens.into_iter().any(|token| match token {
+            TokenTree::Group(group) => uses(group.stream(), generics),
+            TokenTree::Ident(ident) => generics.type_params().any(|param| param.ident == ident),
+            _ => false,
+        })
+    }
+
+    uses(typ.into_token_stream(), generics)
+}
+
+/// Add the `Debug` bound to the type parameters, which is needed to implement `Debug` on the table.
+fn debug_generics(generics: &Generics) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(::std::fmt::Debug));
+    }
+    generics
+}
+
+/// Get the name of the struct of a field with `#[tql(embed)]`.
+fn embedded_struct(field: &Field) -> Option<String> {
+    if !field_attributes(&field.attrs).unwrap_or_default().embed {
+        return None;
+    }
+    match field_ty_to_type(&field.ty).node {
+        Type::UserDefined(typ) => Some(typ),
+        _ => None,
+    }
+}
+
+/// Check if the field is a `PrimaryKey`.
+fn is_primary_key(field: &Field) -> bool {
+    matches!(field_ty_to_type(&field.ty).node, Type::PrimaryKey(_))
+}
+
+/// Check if the field is a `ManyToMany`.
+fn is_many_to_many(field: &Field) -> bool {
+    matches!(field_ty_to_type(&field.ty).node, Type::ManyToMany(_))
+}
+
+/// Check if the field is a `ForeignKey` in an `Option`, whose column can be `NULL`.
+fn is_nullable_foreign_key(field: &Field) -> bool {
+    if let Type::Nullable(ref typ) = field_ty_to_type(&field.ty).node {
+        if let Type::Custom(_) = **typ {
+            return true;
+        }
+    }
+    false
+}
+
+/// Check if the field is a column selected in a query, i.e. it is neither a `ForeignKey` nor a
+/// `ManyToMany`.
+fn is_selected_column(field: &Field) -> bool {
+    foreign_key_path(&field.ty).is_none() && !is_many_to_many(field)
+}
+
+/// Get the path of the table related by a `ForeignKey` type, which is in an `Option` when the
+/// foreign key is nullable.
+fn foreign_key_path(typ: &syn::Type) -> Option<&Path> {
+    if let syn::Type::Path(TypePath { ref path, .. }) = *typ {
+        let segment = path.segments.first()?.into_value();
         if segment.ident == "ForeignKey" {
-            // NOTE: this use the Span call_site() to work-around a privacy issue:
-            // https://github.com/rust-lang/rust/issues/46635
+            return get_type_parameter_as_path(&segment.arguments);
+        }
+        if segment.ident == "Option" {
+            let segment = get_type_parameter_as_path(&segment.arguments)?.segments.first()?.into_value();
+            if segment.ident == "ForeignKey" {
+                return get_type_parameter_as_path(&segment.arguments);
+            }
+        }
+    }
+    None
+}
+
+/// Get the name of the table related by a `ForeignKey` field.
+fn foreign_key_table(field: &Field) -> Option<String> {
+    foreign_key_path(&field.ty)
+        .map(|path| path.segments.first().expect("first segment in path").value().ident.to_string())
+}
+
+/// Check if the field is a `PrimaryKey` whose value is generated by the database, hence not
+/// mandatory in an insert.
+fn is_generated_primary_key(field: &Field) -> bool {
+    field_ty_to_type(&field.ty).node.is_generated()
+}
+
+/// Get the `deleted_at` field of a table with `#[tql(soft_delete)]`, if it has the right type.
+fn soft_delete_field<'a>(fields: &'a [Field], table_attributes: &TableAttributes) -> Option<&'a Field> {
+    if !table_attributes.soft_delete {
+        return None;
+    }
+    fields.iter()
+        .find(|field| field.ident.iter().any(|ident| ident == "deleted_at"))
+        .filter(|field| field_ty_to_type(&field.ty).node == Type::Nullable(Box::new(Type::UtcDateTime)))
+}
+
+/// Check if the field is set to the current time with `#[tql(auto_now)]` or `#[tql(auto_now_add)]`.
+fn is_auto_now(field: &Field) -> bool {
+    let attributes = field_attributes(&field.attrs).unwrap_or_default();
+    attributes.auto_now || attributes.auto_now_add
+}
+
+/// Check if the field is a `String`, which can be nullable.
+fn is_string(field: &Field) -> bool {
+    match field_ty_to_type(&field.ty).node {
+        Type::Nullable(typ) => *typ == Type::String,
+        typ => typ == Type::String,
+    }
+}
+
+/// Check if the field is a `Decimal`, which can be nullable.
+fn is_decimal(field: &Field) -> bool {
+    match field_ty_to_type(&field.ty).node {
+        Type::Nullable(typ) => *typ == Type::Decimal,
+        typ => typ == Type::Decimal,
+    }
+}
+
+/// Check if the type is converted through a wrapper type, because the database libraries do not convert
+/// it, which can be nullable.
+fn is_converted(typ: &syn::Type) -> bool {
+    fn is_converted_type(typ: &Type) -> bool {
+        match *typ {
+            Type::Nullable(ref typ) => is_converted_type(typ),
+            Type::Duration | Type::IpAddr | Type::U16 | Type::U32 | Type::U64 => true,
+            _ => false,
+        }
+    }
+
+    is_cow(typ) || is_converted_type(&field_ty_to_type(typ).node)
+}
+
+/// Check if the type is a `Cow<str>`, which can be nullable.
+fn is_cow(typ: &syn::Type) -> bool {
+    if let syn::Type::Path(TypePath { ref path, .. }) = *typ {
+        if let Some(segment) = path.segments.last() {
+            let segment = segment.into_value();
+            if segment.ident == "Cow" {
+                return true;
+            }
+            if segment.ident == "Option" {
+                if let AngleBracketed(AngleBracketedGenericArguments { ref args, .. }) = segment.arguments {
+                    if let Some(GenericArgument::Type(ref typ)) = args.first().map(Pair::into_value) {
+                        return is_cow(typ);
+                    }
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Check if the field is a `ForeignKey` stored in other fields with `#[tql(foreign_key(…))]`.
+fn is_stored_in_other_fields(field: &Field) -> bool {
+    !field_attributes(&field.attrs).unwrap_or_default().foreign_key.is_empty()
+}
+
+/// The index of a column in a row, after the columns of the previous fields.
+/// The number of columns of an embedded struct is only known by its `SqlEmbed` implementation.
+#[derive(Default)]
+struct ColumnIndex {
+    columns: usize,
+    embedded_types: Vec<syn::Type>,
+}
+
+impl ColumnIndex {
+    /// Skip the columns of the `field`.
+    fn skip(&mut self, field: &Field) {
+        if embedded_struct(field).is_some() {
+            self.embedded_types.push(field.ty.clone());
+        }
+        else if is_selected_column(field) {
+            self.columns += 1;
+        }
+    }
+
+    fn to_tokens(&self) -> Tokens {
+        let columns = create_backend().int_literal(self.columns);
+        let embedded_types = &self.embedded_types;
+        // NOTE: this use the Span call_site() to work-around a privacy issue:
+        // https://github.com/rust-lang/rust/issues/46635
+        quote_spanned! { Span::call_site() =>
+            #columns #(+ <#embedded_types as ::tql::SqlEmbed>::FIELD_COUNT)*
+        }
+    }
+}
+
+fn to_row_get(field: &Field, generics: &Generics, with_delta: bool, index: &mut ColumnIndex) -> Tokens {
+    let typ = &field.ty;
+    let converted = is_converted(typ);
+    let embedded = embedded_struct(field).is_some();
+    let generic = type_parameter(typ, generics).is_some();
+    if foreign_key_path(typ).is_some() {
+        // NOTE: this use the Span call_site() to work-around a privacy issue:
+        // https://github.com/rust-lang/rust/issues/46635
+        return quote_spanned! { Span::call_site() =>
+            None
+        };
+    }
+    if let syn::Type::Path(ref path) = *typ {
+        let segment = path.path.segments.first().expect("first segment").into_value();
+        if segment.ident == "ManyToMany" {
             return quote_spanned! { Span::call_site() =>
-                None
+                Vec::new()
             };
         }
     }
-    let backend = create_backend();
-    let index_lit = backend.int_literal(*index);
-    *index += 1;
+    let index_lit = index.to_tokens();
+    index.skip(field);
     let index_lit =
         if with_delta {
             quote! {
@@ -680,8 +2469,28 @@ fn to_row_get(typ: syn::Type, with_delta: bool, index: &mut usize) -> Tokens {
         };
     // NOTE: this use the Span call_site() to work-around a privacy issue:
     // https://github.com/rust-lang/rust/issues/46635
-    quote_spanned! { Span::call_site() =>
-        __tql_item_row.get(#index_lit)
+    if embedded {
+        quote_spanned! { Span::call_site() =>
+            <#typ as ::tql::SqlEmbed>::from_row(__tql_item_row, #index_lit)
+        }
+    }
+    else if converted {
+        quote_spanned! { Span::call_site() =>
+            ::tql::Convert::from_wrapper(__tql_item_row.get(#index_lit))
+        }
+    }
+    else if generic {
+        quote_spanned! { Span::call_site() =>
+            {
+                let repr: ::tql::Repr<#typ> = __tql_item_row.get(#index_lit);
+                repr.0
+            }
+        }
+    }
+    else {
+        quote_spanned! { Span::call_site() =>
+            __tql_item_row.get(#index_lit)
+        }
     }
 }
 
@@ -744,17 +2553,56 @@ pub fn gen_check_missing_fields(input: TokenStream) -> TokenStream {
     }
 }
 
+pub fn gen_check_max_length(input: TokenStream) -> TokenStream {
+    let args: Arguments = parse(input).expect("parse check_max_length!()");
+    let args = args.0;
+    let field = &args[0];
+    let max_length =
+        if let Expr::Lit(ExprLit { lit: Lit::Int(ref max_length), .. }) = args[1] {
+            max_length.value()
+        }
+        else {
+            panic!("Expecting integer literal");
+        };
+    let literal =
+        match args[2] {
+            Expr::Group(ref group) => &*group.expr,
+            // NOTE: need this condition on stable.
+            ref literal => literal,
+        };
+
+    if let Expr::Lit(ExprLit { lit: Lit::Str(ref string), .. }) = *literal {
+        let length = string.value().chars().count() as u64;
+        if length > max_length {
+            let error = string_literal(&format!(
+                "the string literal assigned to the field `{}` is longer than its max_length ({} > {})",
+                token_to_string(field), length, max_length));
+            return (quote_spanned! { string.span() =>
+                compile_error!(#error);
+            }).into();
+        }
+    }
+    empty_token_stream()
+}
+
 fn rand_string() -> String {
     rand::thread_rng().gen_ascii_chars().take(30).collect()
 }
 
 trait BackendGen {
+    fn connection_type(&self) -> Tokens;
     fn convert_index(&self, index: usize) -> Tokens;
     fn delta_type(&self) -> Tokens;
     fn gen_query_expr(&self, connection_expr: Tokens, args: &SqlQueryWithArgs, args_expr: Tokens, struct_expr: Tokens,
                       aggregate_struct: Tokens, aggregate_expr: Tokens) -> Tokens;
     fn int_literal(&self, num: usize) -> Expr;
+    fn result_type(&self, typ: Tokens) -> Tokens;
     fn row_type_ident(&self, table_ident: &Ident) -> Tokens;
+    fn select_multi(&self, connection_expr: Tokens, sql_query: &Tokens, args_expr: Tokens, struct_expr: Tokens)
+        -> Tokens;
+    fn sql_enum_conversions(&self, type_ident: &Ident, variants: &[Ident]) -> Tokens;
+    fn sql_type_conversions(&self, type_ident: &Ident, repr: &syn::Type) -> Tokens;
     fn to_sql(&self, primary_key_ident: &Ident) -> Tokens;
-    fn to_sql_impl(&self, table_ident: &Ident, to_sql_code: Tokens) -> Tokens;
+    fn to_sql_impl(&self, table_ident: &Ident, generics: &Generics, primary_key_type: Option<&syn::Type>,
+                   to_sql_code: Tokens) -> Tokens;
 }
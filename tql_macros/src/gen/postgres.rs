@@ -23,10 +23,12 @@ use proc_macro2::{Span,TokenStream};
 use syn::{
     Expr,
     ExprLit,
+    Generics,
     Ident,
     IntSuffix,
     Lit,
     LitInt,
+    Type,
 };
 use syn::spanned::Spanned;
 
@@ -42,6 +44,10 @@ pub fn create_backend() -> PostgresBackend {
 }
 
 impl BackendGen for PostgresBackend {
+    fn connection_type(&self) -> TokenStream {
+        quote! { ::postgres::Connection }
+    }
+
     fn convert_index(&self, index: usize) -> TokenStream {
         quote! {
             #index
@@ -58,6 +64,8 @@ impl BackendGen for PostgresBackend {
     {
         let result_ident = Ident::new("__tql_result", proc_macro2::Span::call_site());
         let sql_query = &args.sql;
+        let table_ident = &args.table_name;
+        let table_type = &args.table_type;
         let std_ident = quote_spanned! { connection_expr.span() =>
             ::std
         };
@@ -91,10 +99,18 @@ impl BackendGen for PostgresBackend {
                 }}
             },
             QueryType::Create => {
-                quote! {
-                    #connection_expr.prepare(#sql_query)
+                let create_types_macro_name =
+                    Ident::new(&format!("tql_{}_create_types", table_ident), Span::call_site());
+                // NOTE: the types of the enums used by the table must be created first.
+                quote! {{
+                    let queries: &[&str] = &#create_types_macro_name!();
+                    queries.iter()
+                        .filter(|query| !query.is_empty())
+                        .map(|query| #connection_expr.prepare(query).and_then(|result| result.execute(&[])))
+                        .collect::<#std_ident::result::Result<Vec<_>, _>>()
+                        .and_then(|_| #connection_expr.prepare(#sql_query))
                         .and_then(|result| result.execute(&[]))
-                }
+                }}
             },
             QueryType::InsertOne => {
                 quote! {
@@ -103,21 +119,35 @@ impl BackendGen for PostgresBackend {
                             let rows = result.query(&#args_expr)?;
                             let __tql_item_row = rows.iter().next()
                                 .ok_or_else(|| #std_ident::io::Error::from(#std_ident::io::ErrorKind::NotFound))?;
-                            let count: i32 = __tql_item_row.get(0);
-                            Ok(count)
+                            Ok(<#table_type as ::tql::SqlTable>::primary_key_from_row(&__tql_item_row))
                         })
                 }
             },
-            QueryType::SelectMulti => {
+            QueryType::SelectMulti if !args.joins.is_empty() => {
+                // NOTE: a join on a ManyToMany field returns multiple rows for the same item.
                 quote! {
                     #connection_expr.prepare(#sql_query)
                         .and_then(|#result_ident| {
                             let #result_ident = #result_ident.query(&#args_expr)?;
                             let #result_ident = #result_ident.iter();
-                            Ok(#result_ident.map(|__tql_item_row| {
+                            Ok(<#table_type as ::tql::SqlTable>::merge_rows(#result_ident.map(|__tql_item_row| {
+                                #struct_expr
+                            }).collect::<Vec<_>>()))
+                        })
+                }
+            },
+            QueryType::SelectMulti => self.select_multi(connection_expr, sql_query, args_expr, struct_expr),
+            QueryType::SelectOne if !args.joins.is_empty() => {
+                quote! {
+                    #connection_expr.prepare(#sql_query)
+                        .and_then(|#result_ident| {
+                            let #result_ident = #result_ident.query(&#args_expr)?;
+                            let #result_ident = #result_ident.iter();
+                            <#table_type as ::tql::SqlTable>::merge_rows(#result_ident.map(|__tql_item_row| {
                                 #struct_expr
                             }).collect::<Vec<_>>())
-                            // TODO: return an iterator instead of a vector.
+                                .into_iter().next()
+                                .ok_or_else(|| #std_ident::io::Error::from(#std_ident::io::ErrorKind::NotFound).into())
                         })
                 }
             },
@@ -148,27 +178,162 @@ impl BackendGen for PostgresBackend {
         })
     }
 
+    fn result_type(&self, typ: TokenStream) -> TokenStream {
+        quote! { ::postgres::Result<#typ> }
+    }
+
     fn row_type_ident(&self, table_ident: &Ident) -> proc_macro2::TokenStream {
         quote_spanned! { table_ident.span() =>
             ::postgres::rows::Row
         }
     }
 
+    fn select_multi(&self, connection_expr: TokenStream, sql_query: &TokenStream, args_expr: TokenStream,
+                    struct_expr: TokenStream) -> TokenStream
+    {
+        let result_ident = Ident::new("__tql_result", Span::call_site());
+        quote! {
+            #connection_expr.prepare(#sql_query)
+                .and_then(|#result_ident| {
+                    let #result_ident = #result_ident.query(&#args_expr)?;
+                    let #result_ident = #result_ident.iter();
+                    Ok(#result_ident.map(|__tql_item_row| {
+                        #struct_expr
+                    }).collect::<Vec<_>>())
+                    // TODO: return an iterator instead of a vector.
+                })
+        }
+    }
+
+    fn sql_enum_conversions(&self, type_ident: &Ident, variants: &[Ident]) -> TokenStream {
+        let std_ident = quote_spanned! { type_ident.span() =>
+            ::std
+        };
+        let postgres_ident = quote_spanned! { type_ident.span() =>
+            ::postgres
+        };
+        let type_name = type_ident.to_string();
+        let paths1 = variants.iter().map(|variant| quote! { #type_ident::#variant });
+        let paths2 = paths1.clone();
+        let names1 = variants.iter().map(ToString::to_string);
+        let names2 = names1.clone();
+        // NOTE: the enum type is created with the name of the enum, which PostgreSQL converts to
+        // lowercase.
+        quote! {
+            impl #postgres_ident::types::ToSql for #type_ident {
+                fn to_sql(&self, _ty: &#postgres_ident::types::Type, out: &mut Vec<u8>) ->
+                    Result<#postgres_ident::types::IsNull, Box<#std_ident::error::Error + 'static + Sync + Send>>
+                {
+                    let name =
+                        match *self {
+                            #(#paths1 => #names1,)*
+                        };
+                    out.extend_from_slice(name.as_bytes());
+                    Ok(#postgres_ident::types::IsNull::No)
+                }
+
+                fn accepts(ty: &#postgres_ident::types::Type) -> bool {
+                    ty.name().eq_ignore_ascii_case(#type_name)
+                }
+
+                fn to_sql_checked(&self, ty: &#postgres_ident::types::Type, out: &mut #std_ident::vec::Vec<u8>)
+                    -> #std_ident::result::Result<#postgres_ident::types::IsNull,
+                    Box<#std_ident::error::Error + #std_ident::marker::Sync + #std_ident::marker::Send>>
+                {
+                    #postgres_ident::types::__to_sql_checked(self, ty, out)
+                }
+            }
+
+            impl #postgres_ident::types::FromSql for #type_ident {
+                fn from_sql(_ty: &#postgres_ident::types::Type, raw: &[u8]) ->
+                    Result<Self, Box<#std_ident::error::Error + 'static + Sync + Send>>
+                {
+                    match #std_ident::str::from_utf8(raw)? {
+                        #(#names2 => Ok(#paths2),)*
+                        value => Err(format!("unknown variant {} of {}", value, #type_name).into()),
+                    }
+                }
+
+                fn accepts(ty: &#postgres_ident::types::Type) -> bool {
+                    ty.name().eq_ignore_ascii_case(#type_name)
+                }
+            }
+        }
+    }
+
+    fn sql_type_conversions(&self, type_ident: &Ident, repr: &Type) -> TokenStream {
+        let std_ident = quote_spanned! { type_ident.span() =>
+            ::std
+        };
+        let postgres_ident = quote_spanned! { type_ident.span() =>
+            ::postgres
+        };
+        // NOTE: the value is converted to its representation, so it accepts the same SQL types.
+        quote! {
+            impl #postgres_ident::types::ToSql for #type_ident {
+                fn to_sql(&self, ty: &#postgres_ident::types::Type, out: &mut Vec<u8>) ->
+                    Result<#postgres_ident::types::IsNull, Box<#std_ident::error::Error + 'static + Sync + Send>>
+                {
+                    #postgres_ident::types::ToSql::to_sql(&::tql::SqlType::to_repr(self), ty, out)
+                }
+
+                fn accepts(ty: &#postgres_ident::types::Type) -> bool {
+                    <#repr as #postgres_ident::types::ToSql>::accepts(ty)
+                }
+
+                fn to_sql_checked(&self, ty: &#postgres_ident::types::Type, out: &mut #std_ident::vec::Vec<u8>)
+                    -> #std_ident::result::Result<#postgres_ident::types::IsNull,
+                    Box<#std_ident::error::Error + #std_ident::marker::Sync + #std_ident::marker::Send>>
+                {
+                    #postgres_ident::types::__to_sql_checked(self, ty, out)
+                }
+            }
+
+            impl #postgres_ident::types::FromSql for #type_ident {
+                fn from_sql(ty: &#postgres_ident::types::Type, raw: &[u8]) ->
+                    Result<Self, Box<#std_ident::error::Error + 'static + Sync + Send>>
+                {
+                    <#repr as #postgres_ident::types::FromSql>::from_sql(ty, raw)
+                        .and_then(<Self as ::tql::SqlType>::from_repr)
+                }
+
+                fn accepts(ty: &#postgres_ident::types::Type) -> bool {
+                    <#repr as #postgres_ident::types::FromSql>::accepts(ty)
+                }
+            }
+        }
+    }
+
     fn to_sql(&self, primary_key_ident: &Ident) -> proc_macro2::TokenStream {
         quote! {
             self.#primary_key_ident.to_sql(ty, out)
         }
     }
 
-    fn to_sql_impl(&self, table_ident: &Ident, to_sql_code: proc_macro2::TokenStream) -> TokenStream {
+    fn to_sql_impl(&self, table_ident: &Ident, generics: &Generics, primary_key_type: Option<&Type>,
+                   to_sql_code: proc_macro2::TokenStream) -> TokenStream
+    {
         let std_ident = quote_spanned! { table_ident.span() =>
             ::std
         };
         let postgres_ident = quote_spanned! { table_ident.span() =>
             ::postgres
         };
+        // NOTE: a table is converted to its primary key, so it accepts the same SQL types.
+        let accepts =
+            if let Some(typ) = primary_key_type {
+                quote! {
+                    <#typ as #postgres_ident::types::ToSql>::accepts(ty)
+                }
+            }
+            else {
+                quote! {
+                    *ty == #postgres_ident::types::INT4
+                }
+            };
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
         quote! {
-            impl #postgres_ident::types::ToSql for #table_ident {
+            impl #impl_generics #postgres_ident::types::ToSql for #table_ident #ty_generics #where_clause {
                 fn to_sql(&self, ty: &#postgres_ident::types::Type, out: &mut Vec<u8>) ->
                     Result<#postgres_ident::types::IsNull, Box<#std_ident::error::Error + 'static + Sync + Send>>
                 {
@@ -176,7 +341,7 @@ impl BackendGen for PostgresBackend {
                 }
 
                 fn accepts(ty: &#postgres_ident::types::Type) -> bool {
-                    *ty == #postgres_ident::types::INT4
+                    #accepts
                 }
 
                 fn to_sql_checked(&self, ty: &#postgres_ident::types::Type, out: &mut #std_ident::vec::Vec<u8>)
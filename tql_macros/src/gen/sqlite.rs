@@ -23,10 +23,12 @@ use proc_macro2::{Span,TokenStream};
 use syn::{
     Expr,
     ExprLit,
+    Generics,
     Ident,
     IntSuffix,
     Lit,
     LitInt,
+    Type,
 };
 use syn::spanned::Spanned;
 
@@ -42,6 +44,10 @@ pub fn create_backend() -> SqliteBackend {
 }
 
 impl BackendGen for SqliteBackend {
+    fn connection_type(&self) -> TokenStream {
+        quote! { ::rusqlite::Connection }
+    }
+
     fn convert_index(&self, index: usize) -> TokenStream {
         let index = index as i32;
         quote! {
@@ -58,6 +64,8 @@ impl BackendGen for SqliteBackend {
     {
         let result_ident = Ident::new("__tql_result",Span::call_site());
         let sql_query = &args.sql;
+        let table_ident = &args.table_name;
+        let table_type = &args.table_type;
         let rusqlite_ident = quote_spanned! { connection_expr.span() =>
             ::rusqlite
         };
@@ -92,19 +100,33 @@ impl BackendGen for SqliteBackend {
                 }}
             },
             QueryType::Create => {
+                // NOTE: SQLite only enforces the foreign keys when this pragma is set on the
+                // connection.
                 quote! {
-                    #connection_expr.prepare(#sql_query)
+                    #connection_expr.prepare("PRAGMA foreign_keys = ON")
+                        .and_then(|mut result| result.execute(&[]))
+                        .and_then(|_| #connection_expr.prepare(#sql_query))
                         .and_then(|mut result| result.execute(&[]))
                 }
             },
             QueryType::InsertOne => {
+                // NOTE: the primary key is fetched with the rowid since it is not necessarily an
+                // integer.
+                let primary_key_macro_name = Ident::new(&format!("tql_{}_primary_key_field", table_ident),
+                    Span::call_site());
+                let table_macro_name = Ident::new(&format!("tql_{}_table_name", table_ident), Span::call_site());
                 quote! {
                     #connection_expr.prepare(#sql_query)
                         .and_then(|mut result| result.execute(&#args_expr))
-                        .map(|_| #connection_expr.last_insert_rowid() as i32) // FIXME: don't cast?
+                        .and_then(|_| #connection_expr.query_row(
+                            concat!("SELECT ", #primary_key_macro_name!(), " FROM ", #table_macro_name!(),
+                                    " WHERE rowid = ?"),
+                            &[&#connection_expr.last_insert_rowid()],
+                            |__tql_item_row| <#table_type as ::tql::SqlTable>::primary_key_from_row(__tql_item_row)))
                 }
             },
-            QueryType::SelectMulti => {
+            QueryType::SelectMulti if !args.joins.is_empty() => {
+                // NOTE: a join on a ManyToMany field returns multiple rows for the same item.
                 quote! {
                     #connection_expr.prepare(#sql_query)
                         .and_then(|mut #result_ident| {
@@ -112,8 +134,23 @@ impl BackendGen for SqliteBackend {
                                     #struct_expr
                                 })?;
                             #result_ident.collect::<Result<Vec<_>, _>>()
-                            // TODO: return an iterator instead of a vector.
                         })
+                        .map(<#table_type as ::tql::SqlTable>::merge_rows)
+                }
+            },
+            QueryType::SelectMulti => self.select_multi(connection_expr, sql_query, args_expr, struct_expr),
+            QueryType::SelectOne if !args.joins.is_empty() => {
+                quote! {
+                    #connection_expr.prepare(#sql_query)
+                        .and_then(|mut #result_ident| {
+                            let #result_ident = #result_ident.query_map(&#args_expr, |__tql_item_row| {
+                                    #struct_expr
+                                })?;
+                            #result_ident.collect::<Result<Vec<_>, _>>()
+                        })
+                        .and_then(|items| <#table_type as ::tql::SqlTable>::merge_rows(items).into_iter()
+                            .next()
+                            .ok_or_else(|| #rusqlite_ident::Error::QueryReturnedNoRows))
                 }
             },
             QueryType::SelectOne => {
@@ -144,24 +181,102 @@ impl BackendGen for SqliteBackend {
         })
     }
 
+    fn result_type(&self, typ: TokenStream) -> TokenStream {
+        quote! { ::rusqlite::Result<#typ> }
+    }
+
     fn row_type_ident(&self, table_ident: &Ident) -> TokenStream {
         quote_spanned! { table_ident.span() =>
             ::rusqlite::Row
         }
     }
 
+    fn select_multi(&self, connection_expr: TokenStream, sql_query: &TokenStream, args_expr: TokenStream,
+                    struct_expr: TokenStream) -> TokenStream
+    {
+        let result_ident = Ident::new("__tql_result", Span::call_site());
+        quote! {
+            #connection_expr.prepare(#sql_query)
+                .and_then(|mut #result_ident| {
+                    let #result_ident = #result_ident.query_map(&#args_expr, |__tql_item_row| {
+                            #struct_expr
+                        })?;
+                    #result_ident.collect::<Result<Vec<_>, _>>()
+                    // TODO: return an iterator instead of a vector.
+                })
+        }
+    }
+
+    fn sql_enum_conversions(&self, type_ident: &Ident, variants: &[Ident]) -> TokenStream {
+        let rusqlite_ident = quote_spanned! { type_ident.span() =>
+            ::rusqlite
+        };
+        let paths1 = variants.iter().map(|variant| quote! { #type_ident::#variant });
+        let paths2 = paths1.clone();
+        let names1 = variants.iter().map(ToString::to_string);
+        let names2 = names1.clone();
+        quote! {
+            impl #rusqlite_ident::types::ToSql for #type_ident {
+                fn to_sql(&self) -> #rusqlite_ident::Result<#rusqlite_ident::types::ToSqlOutput> {
+                    let name =
+                        match *self {
+                            #(#paths1 => #names1,)*
+                        };
+                    Ok(#rusqlite_ident::types::ToSqlOutput::Borrowed(#rusqlite_ident::types::ValueRef::Text(name)))
+                }
+            }
+
+            impl #rusqlite_ident::types::FromSql for #type_ident {
+                fn column_result(value: #rusqlite_ident::types::ValueRef) ->
+                    #rusqlite_ident::types::FromSqlResult<Self>
+                {
+                    match value.as_str()? {
+                        #(#names2 => Ok(#paths2),)*
+                        _ => Err(#rusqlite_ident::types::FromSqlError::InvalidType),
+                    }
+                }
+            }
+        }
+    }
+
+    fn sql_type_conversions(&self, type_ident: &Ident, repr: &Type) -> TokenStream {
+        let rusqlite_ident = quote_spanned! { type_ident.span() =>
+            ::rusqlite
+        };
+        quote! {
+            impl #rusqlite_ident::types::ToSql for #type_ident {
+                fn to_sql(&self) -> #rusqlite_ident::Result<#rusqlite_ident::types::ToSqlOutput> {
+                    ::tql::to_sql_output(&::tql::SqlType::to_repr(self))
+                }
+            }
+
+            impl #rusqlite_ident::types::FromSql for #type_ident {
+                fn column_result(value: #rusqlite_ident::types::ValueRef) ->
+                    #rusqlite_ident::types::FromSqlResult<Self>
+                {
+                    <#repr as #rusqlite_ident::types::FromSql>::column_result(value)
+                        .and_then(|repr| <Self as ::tql::SqlType>::from_repr(repr)
+                            .map_err(#rusqlite_ident::types::FromSqlError::Other))
+                }
+            }
+        }
+    }
+
     fn to_sql(&self, primary_key_ident: &Ident) -> TokenStream {
         quote! {
             self.#primary_key_ident.to_sql()
         }
     }
 
-    fn to_sql_impl(&self, table_ident: &Ident, to_sql_code: TokenStream) -> TokenStream {
+    fn to_sql_impl(&self, table_ident: &Ident, generics: &Generics, _primary_key_type: Option<&Type>,
+                   to_sql_code: TokenStream) -> TokenStream
+    {
         let rusqlite_ident = quote_spanned! { table_ident.span() =>
             ::rusqlite
         };
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
         quote! {
-            impl #rusqlite_ident::types::ToSql for #table_ident {
+            impl #impl_generics #rusqlite_ident::types::ToSql for #table_ident #ty_generics #where_clause {
                 fn to_sql(&self) -> #rusqlite_ident::Result<#rusqlite_ident::types::ToSqlOutput>
                 {
                     #to_sql_code
@@ -20,7 +20,6 @@
  */
 
 /*
- * TODO: ManyToMany.
  * TODO: looks like the function annotate does not exist anymore.
  * TODO: return an iterator instead of a Vec.
  * TODO: add support for default value.
@@ -28,7 +27,6 @@
  *
  * TODO: support the missing types
  * (https://docs.rs/postgres/0.15.1/postgres/types/trait.ToSql.html).
- * TODO: support intermediate models in ManyToMany (allow adding extra fields).
  * TODO: error for unsupported types in backends.
  * TODO: remove useless empty string ("") in generated code (concat!("", "")).
  * TODO: avoid using quote_spanned and respan when possible and document all of their usage.
@@ -39,25 +37,18 @@
  * TODO: show a better error when using a type that is not a table (both in ForeignKey<_> and in
  * sql!(_.all())).
  * FIXME: escape name like `Table` to avoid error.
- * FIXME: error when having mutiple ForeignKey with the same table (then support having multiple
- * ForeignKey).
  * TODO: document the management of the connection.
  * TODO: use as_ref() for Ident instead of &ident.to_string().
- * TODO: support recursive foreign key.
  * TODO: write fail tests for stable using include!().
  * TODO: try to hide Option in the mismatched type error message for ForeignKey.
  * TODO: use fully-qualified name everywhere in the query (aggregate, …).
  *
- * TODO: support other types (uuid, string) for the primary key, possibly by making it generic.
  * TODO: allow using other fields in filter(), update(), … like F() expressions in Django
  ** Table.filter(field1 > Table.field2) may not work.
  ** Table.filter(field1 > $field2)
- * TODO: unique constraints.
- * TODO: support primary key with multiple columns.
  * TODO: allow selecting only some fields.
  * TODO: join on non foreign key.
  * TODO: allow user-defined functions (maybe with partial query?) and types.
- * TODO: add table_name attribute to allow changing the table name.
  *
  * TODO: remove allow_failure for beta when this issue is fixed:
  * https://github.com/rust-lang/rust/issues/46478
@@ -117,9 +108,11 @@ use proc_macro2::TokenStream as Tokens;
 
 use syn::{
     Expr,
+    ExprLit,
     Ident,
     Item,
     ItemEnum,
+    Lit,
     parse,
     parse2,
 };
@@ -153,10 +146,14 @@ use error::{Error, Result};
 #[cfg(not(feature = "unstable"))]
 use error::compiler_error;
 use gen::{
+    gen_check_max_length,
     gen_check_missing_fields,
     generate_errors,
     gen_query,
     get_struct_fields,
+    sql_embed_impl,
+    sql_enum_impl,
+    sql_type_impl,
     table_macro,
     table_methods,
     tosql_impl,
@@ -181,6 +178,7 @@ struct SqlQueryWithArgs {
     sql: Tokens,
     stable_macro_query: Tokens,
     table_name: Ident,
+    table_type: Tokens,
 }
 
 /// Expand the `sql!()` macro.
@@ -245,6 +243,15 @@ fn to_sql_query(input: proc_macro2::TokenStream) -> Result<SqlQueryWithArgs> {
     let parser = Parser::new();
     let method_calls = parser.parse(&expr)?;
     let table_name = method_calls.name.clone().expect("table name in method_calls");
+    // NOTE: the generic arguments of the table are inferred when they are not specified.
+    let table_type =
+        match method_calls.generic_arguments {
+            Some(ref arguments) => quote! { #table_name #arguments },
+            None => {
+                let macro_name = Ident::new(&format!("tql_{}_type", table_name), Span::call_site());
+                quote! { #macro_name!() }
+            },
+        };
     #[cfg(feature = "unstable")]
     let insert_call_span = get_insert_position(&method_calls);
     let mut query = analyze(&method_calls)?;
@@ -265,6 +272,9 @@ fn to_sql_query(input: proc_macro2::TokenStream) -> Result<SqlQueryWithArgs> {
     let query_type = query_type(&query);
     let mut idents = get_sort_idents(&query);
     idents.extend(get_values_idents(&query));
+    if let Query::Relation { ref field, .. } = query {
+        idents.push(field.clone());
+    }
     let insert_idents = get_insert_idents(&query);
     let limit_exprs = get_limit_args(&query);
     let filter_method_calls = get_method_calls(&query);
@@ -287,12 +297,13 @@ fn to_sql_query(input: proc_macro2::TokenStream) -> Result<SqlQueryWithArgs> {
         sql,
         stable_macro_query,
         table_name,
+        table_type,
     })
 }
 
 /// Expand the `#[SqlTable]` attribute.
 /// This attribute must be used on structs to tell tql that it represents an SQL table.
-#[proc_macro_derive(SqlTable)]
+#[proc_macro_derive(SqlTable, attributes(tql))]
 pub fn sql_table(input: TokenStream) -> TokenStream {
     let item: Item =
         match parse(input) {
@@ -337,6 +348,94 @@ pub fn sql_table(input: TokenStream) -> TokenStream {
     gen
 }
 
+/// Expand the `#[derive(SqlType)]` attribute.
+/// This attribute must be used on structs to tell tql that they can be used as the type of a field.
+#[proc_macro_derive(SqlType, attributes(tql))]
+pub fn sql_type(input: TokenStream) -> TokenStream {
+    let item: Item =
+        match parse(input) {
+            Ok(item) => item,
+            Err(error) => return generate_errors(vec![Error::new(
+                    &format!("cannot parse expression in SqlType: {}", error), Span::call_site())]),
+        };
+
+    let mut compiler_errors = quote! {};
+    if let Item::Struct(item_struct) = item {
+        match sql_type_impl(&item_struct) {
+            Ok(code) => return code.into(),
+            Err(errors) => {
+                for error in errors {
+                    add_error(error, &mut compiler_errors);
+                }
+            },
+        }
+    }
+    else {
+        let error = Error::new("Expected struct but found", item.span()); // TODO: improve this message.
+        add_error(error, &mut compiler_errors);
+    }
+    compiler_errors.into()
+}
+
+/// Expand the `#[derive(SqlEmbed)]` attribute.
+/// This attribute must be used on structs to store their fields in the columns of the tables having a field of this
+/// type with `#[tql(embed)]`.
+#[proc_macro_derive(SqlEmbed, attributes(tql))]
+pub fn sql_embed(input: TokenStream) -> TokenStream {
+    let item: Item =
+        match parse(input) {
+            Ok(item) => item,
+            Err(error) => return generate_errors(vec![Error::new(
+                    &format!("cannot parse expression in SqlEmbed: {}", error), Span::call_site())]),
+        };
+
+    let mut compiler_errors = quote! {};
+    if let Item::Struct(item_struct) = item {
+        match sql_embed_impl(&item_struct) {
+            Ok(code) => return code.into(),
+            Err(errors) => {
+                for error in errors {
+                    add_error(error, &mut compiler_errors);
+                }
+            },
+        }
+    }
+    else {
+        let error = Error::new("Expected struct but found", item.span()); // TODO: improve this message.
+        add_error(error, &mut compiler_errors);
+    }
+    compiler_errors.into()
+}
+
+/// Expand the `#[derive(SqlEnum)]` attribute.
+/// This attribute must be used on enums without fields to store them in a column.
+#[proc_macro_derive(SqlEnum, attributes(tql))]
+pub fn sql_enum(input: TokenStream) -> TokenStream {
+    let item: Item =
+        match parse(input) {
+            Ok(item) => item,
+            Err(error) => return generate_errors(vec![Error::new(
+                    &format!("cannot parse expression in SqlEnum: {}", error), Span::call_site())]),
+        };
+
+    let mut compiler_errors = quote! {};
+    if let Item::Enum(item_enum) = item {
+        match sql_enum_impl(&item_enum) {
+            Ok(code) => return code.into(),
+            Err(errors) => {
+                for error in errors {
+                    add_error(error, &mut compiler_errors);
+                }
+            },
+        }
+    }
+    else {
+        let error = Error::new("Expected enum but found", item.span()); // TODO: improve this message.
+        add_error(error, &mut compiler_errors);
+    }
+    compiler_errors.into()
+}
+
 #[cfg(feature = "unstable")]
 fn respan_tokens_with(tokens: Tokens, span: proc_macro::Span) -> Tokens {
     let tokens: proc_macro2::TokenStream = respan_with(tokens.into(), span).into();
@@ -363,16 +462,23 @@ fn respan_with(tokens: TokenStream, span: proc_macro::Span) -> TokenStream {
 
 /// Get the arguments to send to the `postgres::stmt::Statement::query` or
 /// `postgres::stmt::Statement::execute` method.
-fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
+fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>, Vec<Tokens>, Vec<Tokens>) {
     let table_ident = &args.table_name;
+    let table_type = &args.table_type;
     let mut arg_refs = vec![];
     let mut fns = vec![];
     let mut assigns = vec![];
     let mut typechecks = vec![];
+    let mut validations = vec![];
+    let mut length_checks = vec![];
     #[cfg(not(feature = "unstable"))]
     let mut metavars = vec![];
     #[cfg(feature = "unstable")]
     let metavars = vec![];
+    #[cfg(not(feature = "unstable"))]
+    let bindings = vec![];
+    #[cfg(feature = "unstable")]
+    let mut bindings = vec![];
     let mut next_name = (0..).map(|counter|
         Ident::new(&format!("__tql_arg{}", counter), Span::call_site())
     );
@@ -385,7 +491,7 @@ fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
                     Expr::Lit(_) => None,
                     _ => Some(next_name.next().expect("Next name")),
                 };
-            if let Some(name) = arg.field_name.as_ref()
+            let name = arg.field_name.as_ref()
                 .map(|name| {
                     let pos = name.span();
                     let name = name.to_string();
@@ -393,8 +499,18 @@ fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
                         .map(|index| index + 1)
                         .unwrap_or(0);
                     Ident::new(&name[index..], pos)
-                })
-            {
+                });
+            let embedded_macro_name = Ident::new(&format!("tql_{}_embedded_field", table_ident), Span::call_site());
+            let field =
+                match (name, &arg.relation) {
+                    // NOTE: the relation is either a ManyToMany field or an embedded field.
+                    (Some(name), Some(relation)) => Some(quote! { #embedded_macro_name!(item #ident #relation).#name }),
+                    (Some(name), None) => Some(quote! { #ident.#name }),
+                    // NOTE: the argument is the primary key of an item related by a ManyToMany field.
+                    (None, Some(relation)) => Some(quote! { *::tql::related_primary_key(&mut #ident.#relation) }),
+                    (None, None) => None,
+                };
+            if let Some(field) = field {
                 let convert_ident = Ident::new("__tql_convert", arg.expression.span());
                 let to_owned_ident = Ident::new("to_owned", Span::call_site());
                 #[cfg(not(feature = "unstable"))]
@@ -405,8 +521,37 @@ fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
                 #[cfg(feature = "unstable")]
                 let expr = &arg.expression;
                 assigns.push(quote_spanned! { arg.expression.span() =>
-                    #ident.#name = #convert_ident(&#expr.#to_owned_ident());
+                    #field = #convert_ident(&#expr.#to_owned_ident());
                 });
+                if arg.assigned && arg.relation.is_none() {
+                    let validate_macro_name = Ident::new(&format!("tql_{}_validate", table_ident),
+                                                         Span::call_site());
+                    let name = arg.field_name.as_ref().expect("assigned field name");
+                    // NOTE: the expression is bound once, so that the validated value is the value sent
+                    // to the database.
+                    #[cfg(feature = "unstable")]
+                    let expr =
+                        if let Some(ref arg_name) = arg_name {
+                            bindings.push(quote_spanned! { arg.expression.span() =>
+                                let #arg_name = &(#expr);
+                            });
+                            quote! { #arg_name }
+                        }
+                        else {
+                            quote! { #expr }
+                        };
+                    validations.push(quote! {
+                        #validate_macro_name!(#name, #expr)
+                    });
+                    // NOTE: the length of a string literal is checked at compile time.
+                    if let Expr::Lit(ExprLit { lit: Lit::Str(ref string), .. }) = arg.expression {
+                        let check_max_length_macro_name =
+                            Ident::new(&format!("tql_{}_check_max_length", table_ident), Span::call_site());
+                        length_checks.push(quote! {
+                            #check_max_length_macro_name!(#name, #string);
+                        });
+                    }
+                }
                 fns.push(quote_spanned! { arg.expression.span() =>
                     // NOTE: hack to get the type required by the field struct.
                     fn #convert_ident<T: ::std::ops::Deref>(_arg: T) -> T::Target
@@ -429,17 +574,24 @@ fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
                     {
                         if let Some(name) = _name {
                             metavars.push(quote! { #name });
-                            arg_refs.push(quote! { &#name })
+                            arg_refs.push(field_argument(table_ident, arg, quote! { &#name }))
                         }
                         else {
                             let expr = &arg.expression;
-                            arg_refs.push(quote! { &(#expr) });
+                            arg_refs.push(field_argument(table_ident, arg, quote! { &(#expr) }));
                         }
                     }
                     #[cfg(feature = "unstable")]
                     {
-                        let expr = &arg.expression;
-                        arg_refs.push(quote! { &(#expr) });
+                        match _name {
+                            // NOTE: the validated argument is already bound to a reference.
+                            Some(ref name) if arg.assigned && arg.relation.is_none() =>
+                                arg_refs.push(field_argument(table_ident, arg, quote! { #name })),
+                            _ => {
+                                let expr = &arg.expression;
+                                arg_refs.push(field_argument(table_ident, arg, quote! { &(#expr) }));
+                            },
+                        }
                     }
                 },
             }
@@ -449,6 +601,7 @@ fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
             add_arg(&arg);
         }
     }
+    typechecks.extend(length_checks);
 
     for name in &args.idents {
         typechecks.push(quote_spanned! { name.span() =>
@@ -520,12 +673,26 @@ fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
         ::tql::SqlTable
     };
 
+    let arguments =
+        if let Some(ref insert_idents) = args.insert_idents {
+            // NOTE: the primary key generated by tql is added to the arguments of insert().
+            let macro_name = Ident::new(&format!("tql_{}_generated_primary_key", table_ident), Span::call_site());
+            quote! {
+                #macro_name!(arguments [#(#insert_idents),*] [#(#arg_refs),*])
+            }
+        }
+        else {
+            quote! {
+                [#(#arg_refs),*]
+            }
+        };
+
     let tokens = quote_spanned! { table_ident.span() => {
         // Type check the arguments by creating a dummy struct.
         // TODO: check that this let is not in the generated binary.
         {
             let _tql_closure = || {
-                let mut #ident = <#table_ident as #trait_ident>::_tql_default();
+                let mut #ident = <#table_type as #trait_ident>::_tql_default();
                 #({
                     #fns
                     #assigns
@@ -534,9 +701,24 @@ fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
             };
         }
 
-        [#(#arg_refs),*]
+        #arguments
     }};
-    (tokens, metavars)
+    (tokens, metavars, bindings, validations)
+}
+
+/// Convert the reference to an argument given to a field of the table with the per-table macro, because the
+/// type of the column can be different from the type of the field (e.g. for an unsigned integer or a `Duration`).
+fn field_argument(table_ident: &Ident, arg: &Arg, arg_ref: Tokens) -> Tokens {
+    let macro_name = Ident::new(&format!("tql_{}_argument", table_ident), Span::call_site());
+    match (&arg.field_name, &arg.relation) {
+        (Some(name), None) => quote! {
+            #macro_name!(#name, #arg_ref)
+        },
+        (Some(name), Some(relation)) => quote! {
+            #macro_name!(#relation.#name, #arg_ref)
+        },
+        (None, _) => arg_ref,
+    }
 }
 
 fn concat_token_stream(stream1: TokenStream, stream2: TokenStream) -> TokenStream {
@@ -569,6 +751,12 @@ pub fn check_missing_fields(input: TokenStream) -> TokenStream {
     gen_check_missing_fields(input)
 }
 
+#[cfg(feature = "unstable")]
+#[proc_macro]
+pub fn check_max_length(input: TokenStream) -> TokenStream {
+    gen_check_max_length(input)
+}
+
 struct Arguments(Punctuated<Expr, Token![,]>);
 
 impl syn::parse::Parse for Arguments {
@@ -613,6 +801,33 @@ pub fn stable_check_missing_fieds(input: TokenStream) -> TokenStream {
     empty_token_stream()
 }
 
+#[proc_macro_derive(StableCheckMaxLength)]
+pub fn stable_check_max_length(input: TokenStream) -> TokenStream {
+    let enumeration: Item = parse(input).unwrap();
+    if let Item::Enum(ItemEnum { ref variants, .. }) = enumeration {
+        let variant = &variants.first().unwrap().value().discriminant;
+        if let Expr::Field(ref field) = variant.as_ref().unwrap().1 {
+            if let Expr::Tuple(ref tuple) = *field.base {
+                if let Expr::Macro(ref macr) = **tuple.elems.first().unwrap().value() {
+                    let code = gen_check_max_length(macr.mac.tts.clone().into());
+                    let code = proc_macro2::TokenStream::from(code);
+
+                    let gen = quote! {
+                        macro_rules! __tql_call_macro_max_length {
+                            () => {{
+                                #code
+                            }};
+                        }
+                    };
+                    return gen.into();
+                }
+            }
+        }
+    }
+
+    empty_token_stream()
+}
+
 // TODO: make this function more robust.
 #[proc_macro_derive(StableToSql)]
 pub fn stable_to_sql(input: TokenStream) -> TokenStream {
@@ -31,7 +31,7 @@ pub fn add_method<'a, T: Into<Option<&'a str>>>(object_type: &Type, return_type:
                                                 method: &str, template: T)
 {
     let methods = methods_singleton();
-    methods.insert(method.to_string(), SqlMethodTypes {
+    methods.entry(method.to_string()).or_default().push(SqlMethodTypes {
         argument_types,
         object_type: object_type.clone(),
         return_type,
@@ -106,6 +106,47 @@ pub fn add_initial_methods() {
     #[cfg(feature = "rusqlite")]
     add_method(&Type::String, Type::Bool, vec![Type::String], "iregex", "$0 LIKE $1");
 
+    // JSON methods.
+    #[cfg(feature = "postgres")]
+    add_method(&Type::Json, Type::String, vec![Type::String], "get", "$0 ->> $1");
+    #[cfg(feature = "rusqlite")]
+    add_method(&Type::Json, Type::String, vec![Type::String], "get", "JSON_EXTRACT($0, '$.\"' || $1 || '\"')");
+
+    #[cfg(feature = "postgres")]
+    add_method(&Type::Json, Type::Bool, vec![Type::String], "has_key", "$0 ? $1");
+    #[cfg(feature = "rusqlite")]
+    add_method(&Type::Json, Type::Bool, vec![Type::String], "has_key",
+               "JSON_TYPE($0, '$.\"' || $1 || '\"') IS NOT NULL");
+
+    #[cfg(feature = "postgres")]
+    add_method(&Type::Json, Type::Bool, vec![Type::Json], "contains_json", "$0 @> $1");
+    #[cfg(feature = "rusqlite")]
+    add_method(&Type::Json, Type::Bool, vec![Type::Json], "contains_json", None);
+
+    // Array methods.
+    #[cfg(any(feature = "postgres", feature = "rusqlite"))]
+    let array_type = Type::Array(Box::new(Type::Generic));
+    #[cfg(feature = "postgres")]
+    add_method(&array_type, Type::Bool, vec![Type::Generic], "contains_elem", "$1 = ANY($0)");
+    #[cfg(feature = "rusqlite")]
+    add_method(&array_type, Type::Bool, vec![Type::Generic], "contains_elem", None);
+
+    #[cfg(feature = "postgres")]
+    add_method(&array_type, Type::I32, vec![], "len", "CARDINALITY($0)");
+    #[cfg(feature = "rusqlite")]
+    add_method(&array_type, Type::I32, vec![], "len", None);
+
+    #[cfg(feature = "postgres")]
+    add_method(&array_type, Type::Bool, vec![array_type.clone()], "overlaps", "$0 && $1");
+    #[cfg(feature = "rusqlite")]
+    add_method(&array_type, Type::Bool, vec![array_type.clone()], "overlaps", None);
+
+    // Network address methods.
+    #[cfg(feature = "postgres")]
+    add_method(&Type::IpAddr, Type::Bool, vec![Type::String], "in_subnet", "$0 <<= $1::TEXT::INET");
+    #[cfg(feature = "rusqlite")]
+    add_method(&Type::IpAddr, Type::Bool, vec![Type::String], "in_subnet", None);
+
     // Option methods.
     add_method(&Type::Nullable(Box::new(Type::Generic)), Type::Bool, vec![], "is_some", "$0 IS NOT NULL");
     add_method(&Type::Nullable(Box::new(Type::Generic)), Type::Bool, vec![], "is_none", "$0 IS NULL");
@@ -73,6 +73,7 @@ pub fn optimize(query: &mut Query) {
         Query::Delete { .. } => (), // TODO
         Query::Drop { .. } => (), // Nothing to optimize.
         Query::Insert { .. } => (), // TODO
+        Query::Relation { .. } => (), // Nothing to optimize.
         Query::Select { ref mut limit, .. } => {
             *limit = optimize_limit(limit);
         },
@@ -21,8 +21,11 @@
 
 use proc_macro2::Span;
 use syn::{
+    AngleBracketedGenericArguments,
     Expr,
     Ident,
+    Member,
+    PathArguments,
 };
 use syn::spanned::Spanned;
 
@@ -41,19 +44,25 @@ pub struct MethodCall {
 #[derive(Debug)]
 pub struct MethodCalls {
     pub calls: Vec<MethodCall>,
+    /// The generic arguments of the table, like `<Theme>` in `Setting::<Theme>.all()`.
+    pub generic_arguments: Option<AngleBracketedGenericArguments>,
     /// The identifier at the start of the calls chain.
     pub name: Option<Ident>,
     pub position: Span,
+    /// The `ManyToMany` field accessed in the calls chain, with the number of calls before it.
+    pub relation: Option<(Ident, usize)>,
 }
 
 impl MethodCalls {
     fn new(expr: &Expr) -> Self {
         Self {
             calls: vec![],
+            generic_arguments: None,
             name:  None,
             // NOTE: we only want the position of the first token since this position is used in
             // errors for the table name.
             position: first_token_span(expr),
+            relation: None,
         }
     }
 
@@ -94,9 +103,19 @@ impl Parser {
                 },
                 Expr::Path(ref path) => {
                     if path.path.segments.len() == 1 {
-                        calls.name = Some(path.path.segments.first()
-                            .expect("first segment in path").into_value()
-                            .ident.clone());
+                        let segment = path.path.segments.first().expect("first segment in path").into_value();
+                        calls.name = Some(segment.ident.clone());
+                        if let PathArguments::AngleBracketed(ref arguments) = segment.arguments {
+                            calls.generic_arguments = Some(arguments.clone());
+                        }
+                    }
+                },
+                Expr::Field(ref field) => {
+                    add_calls(&field.base, calls, errors);
+                    match field.member {
+                        Member::Named(ref ident) if calls.relation.is_none() =>
+                            calls.relation = Some((ident.clone(), calls.calls.len())),
+                        _ => errors.push(Error::new("Expected method call", field.member.span())),
                     }
                 },
                 Expr::Index(ref index) => {
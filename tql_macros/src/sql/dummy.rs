@@ -24,7 +24,8 @@
 use proc_macro2::TokenStream ;
 
 use ast::Aggregate;
-use sql::{SqlBackend, ToSql};
+use sql::{SqlBackend, SqlEnum};
+use types::Type;
 
 pub struct DummySqlBackend {}
 
@@ -32,14 +33,22 @@ pub fn create_sql_backend() -> DummySqlBackend {
     DummySqlBackend { }
 }
 
-impl ToSql for Aggregate {
-    fn to_sql(&self, _index: &mut usize) -> String {
+impl Aggregate {
+    pub fn to_tokens(&self, _table: &str) -> TokenStream {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
 }
 
 impl SqlBackend for DummySqlBackend {
-    fn insert_query(&self, _table: &str, _fields: &[String], _values: &[String]) -> TokenStream {
+    fn current_timestamp(&self, _typ: &Type) -> Option<&'static str> {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
+    fn enum_to_sql(&self, _name: &str, _variants: &[String]) -> SqlEnum {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
+    fn insert_query(&self, _table: &str, _fields: TokenStream, _values: TokenStream) -> TokenStream {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
 }
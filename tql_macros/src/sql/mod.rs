@@ -31,9 +31,10 @@ mod sqlite;
 use std::iter;
 use std::str::from_utf8;
 
-use proc_macro2::{Span,TokenStream};
+use proc_macro2::{Literal, Span, TokenStream};
 use syn::{Expr, Ident, Lit};
 
+use analyzer::get_insert_idents;
 use ast::{
     Aggregate,
     AggregateFilter,
@@ -41,6 +42,7 @@ use ast::{
     AggregateFilterExpression,
     Assignment,
     AssignmentOperator,
+    Deleted,
     Expression,
     Filter,
     FilterExpression,
@@ -52,8 +54,8 @@ use ast::{
     MethodCall,
     Order,
     Query,
+    RelationAction,
     RelationalOperator,
-    TypedField,
 };
 use ast::Limit::{
     EndRange,
@@ -63,8 +65,9 @@ use ast::Limit::{
     Range,
     StartRange,
 };
+use attribute::FieldAttributes;
 use plugin::string_literal;
-use state::methods_singleton;
+use state::{SqlMethodTypes, methods_singleton};
 use types::Type;
 
 #[cfg(not(any(feature = "rusqlite", feature = "postgres")))]
@@ -75,7 +78,39 @@ use self::postgres::create_sql_backend;
 use self::sqlite::create_sql_backend;
 
 trait SqlBackend {
-    fn insert_query(&self, table: &str, fields: &[String], values: &[String]) -> TokenStream;
+    fn current_timestamp(&self, typ: &Type) -> Option<&'static str>;
+    fn enum_to_sql(&self, name: &str, variants: &[String]) -> SqlEnum;
+    fn insert_query(&self, table: &str, fields: TokenStream, values: TokenStream) -> TokenStream;
+}
+
+/// The SQL of an enum with `#[derive(SqlEnum)]`.
+pub struct SqlEnum {
+    /// The type of the columns storing the enum.
+    pub column_type: String,
+    /// The condition on the column checking that it stores a variant, when its type does not.
+    pub check: Option<String>,
+    /// The query creating the type of the columns before the tables using it.
+    pub create_query: Option<String>,
+}
+
+/// Convert an enum with `#[derive(SqlEnum)]` to SQL.
+pub fn enum_to_sql(name: &str, variants: &[String]) -> SqlEnum {
+    create_sql_backend().enum_to_sql(name, variants)
+}
+
+/// Get the SQL expression giving the current time stored in a column of type `typ`, for the fields with
+/// `#[tql(auto_now)]` or `#[tql(auto_now_add)]`, if this type can be used for these fields.
+pub fn current_timestamp(typ: &Type) -> Option<&'static str> {
+    create_sql_backend().current_timestamp(typ)
+}
+
+/// Convert the variants of an enum to the list of their SQL values.
+#[cfg(any(feature = "rusqlite", feature = "postgres"))]
+fn variants_to_sql(variants: &[String]) -> String {
+    variants.iter()
+        .map(|variant| format!("'{}'", variant))
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 /// A generic trait for converting a value to SQL.
@@ -83,64 +118,73 @@ pub trait ToSql {
     fn to_sql(&self, index: &mut usize) -> String;
 }
 
-impl ToSql for [Aggregate] {
-    fn to_sql(&self, index: &mut usize) -> String {
-        self.iter().map(|aggregate| aggregate.to_sql(index)).collect::<Vec<_>>().join(", ")
-    }
+fn aggregates_to_tokens(aggregates: &[Aggregate], table: &str) -> TokenStream {
+    sep_by(aggregates.iter().map(|aggregate| aggregate.to_tokens(table)), ", ")
 }
 
-impl ToSql for AggregateFilter {
-    fn to_sql(&self, index: &mut usize) -> String {
-        self.operand1.to_sql(index) + " " +
-            &self.operator.to_sql(index) + " " +
-            &self.operand2.to_sql(index)
+impl AggregateFilter {
+    fn to_tokens(&self, table: &str, index: &mut usize) -> TokenStream {
+        let operand1 = self.operand1.to_tokens(table);
+        let operator = self.operator.to_sql(index);
+        let operand2 = self.operand2.to_sql(index);
+        quote! {
+            #operand1, " ", #operator, " ", #operand2
+        }
     }
 }
 
-impl ToSql for AggregateFilterExpression {
-    fn to_sql(&self, index: &mut usize) -> String {
+impl AggregateFilterExpression {
+    fn to_tokens(&self, table: &str, index: &mut usize) -> TokenStream {
         match *self {
-            AggregateFilterExpression::Filter(ref filter) => filter.to_sql(index),
-            AggregateFilterExpression::Filters(ref filters) => filters.to_sql(index),
-            AggregateFilterExpression::NegFilter(ref filter) =>
-                "NOT ".to_string() +
-                &filter.to_sql(index),
-            AggregateFilterExpression::NoFilters => "".to_string(),
-            AggregateFilterExpression::ParenFilter(ref filter) =>
-                "(".to_string() +
-                &filter.to_sql(index) +
-                ")",
-            AggregateFilterExpression::FilterValue(ref filter_value) => filter_value.node.to_sql(index),
+            AggregateFilterExpression::Filter(ref filter) => filter.to_tokens(table, index),
+            AggregateFilterExpression::Filters(ref filters) => filters.to_tokens(table, index),
+            AggregateFilterExpression::NegFilter(ref filter) => {
+                let filter = filter.to_tokens(table, index);
+                quote! {
+                    "NOT ", #filter
+                }
+            },
+            AggregateFilterExpression::NoFilters => quote! { "" },
+            AggregateFilterExpression::ParenFilter(ref filter) => {
+                let filter = filter.to_tokens(table, index);
+                quote! {
+                    "(", #filter, ")"
+                }
+            },
+            AggregateFilterExpression::FilterValue(ref filter_value) => filter_value.node.to_tokens(table),
         }
     }
 }
 
-impl ToSql for AggregateFilters {
-    fn to_sql(&self, index: &mut usize) -> String {
-        self.operand1.to_sql(index) + " " +
-            &self.operator.to_sql(index) + " " +
-            &self.operand2.to_sql(index)
+impl AggregateFilters {
+    fn to_tokens(&self, table: &str, index: &mut usize) -> TokenStream {
+        let operand1 = self.operand1.to_tokens(table, index);
+        let operator = self.operator.to_sql(index);
+        let operand2 = self.operand2.to_tokens(table, index);
+        quote! {
+            #operand1, " ", #operator, " ", #operand2
+        }
     }
 }
 
-impl ToSql for Assignment {
-    fn to_sql(&self, index: &mut usize) -> String {
-        let identifier = self.identifier.clone().expect("Assignment identifier").to_sql(index);
-        if let AssignmentOperator::Equal = self.operator.node {
-            identifier + &self.operator.node.to_sql(index) + &self.value.to_sql(index)
-        }
-        else {
-            identifier.clone() +
-                &self.operator.node.to_sql(index).replace("{}", &identifier) +
-                &self.value.to_sql(index)
+impl Assignment {
+    fn to_tokens(&self, table: &str, index: &mut usize) -> TokenStream {
+        let identifier = assignment_column_tokens(table, self);
+        let value = self.value.to_sql(index);
+        // In the operator template, {} represents the column itself.
+        let operator = self.operator.node.to_sql(index);
+        let operator = join_tokens(operator.split("{}").map(string_token).collect(), &identifier);
+        quote! {
+            #identifier, #operator, #value
         }
     }
 }
 
-impl ToSql for [Assignment] {
-    fn to_sql(&self, index: &mut usize) -> String {
-        self.iter().map(|assignment| assignment.to_sql(index)).collect::<Vec<_>>().join(", ")
-    }
+fn assignments_to_tokens(assignments: &[Assignment], table: &str, index: &mut usize) -> TokenStream {
+    let assignments: Vec<_> = assignments.iter()
+        .map(|assignment| assignment.to_tokens(table, index))
+        .collect();
+    sep_by(assignments.into_iter(), ", ")
 }
 
 impl ToSql for AssignmentOperator {
@@ -157,24 +201,30 @@ impl ToSql for AssignmentOperator {
 }
 
 impl FilterExpression {
-    fn to_tokens(&self, index: &mut usize) -> TokenStream {
+    fn to_tokens(&self, table: &str, index: &mut usize) -> TokenStream {
         match *self {
-            FilterExpression::Filter(ref filter) => filter.to_tokens(index),
-            FilterExpression::Filters(ref filters) => filters.to_tokens(index),
+            FilterExpression::Filter(ref filter) => filter.to_tokens(table, index),
+            FilterExpression::Filters(ref filters) => filters.to_tokens(table, index),
             FilterExpression::NegFilter(ref filter) => {
-                let filter = filter.to_tokens(index);
+                let filter = filter.to_tokens(table, index);
                 quote! {
                     "NOT ", #filter
                 }
             },
             FilterExpression::NoFilters => quote! { "" }, // No filters result in no SQL code.
             FilterExpression::ParenFilter(ref filter) => {
-                let filter = filter.to_tokens(index);
+                let filter = filter.to_tokens(table, index);
                 quote! {
                     "(", #filter, ")"
                 }
             }
-            FilterExpression::FilterValue(ref filter_value) => filter_value.node.to_tokens(),
+            FilterExpression::FilterValue(ref filter_value) => {
+                let filter_value_tokens = filter_value.node.to_tokens(table, index);
+                let end = filter_value.node.end_tokens();
+                quote! {
+                    #filter_value_tokens #end
+                }
+            },
         }
     }
 }
@@ -219,21 +269,20 @@ impl Join {
     fn to_tokens(&self) -> TokenStream {
         let related_table_macro_name =
             Ident::new(&format!("tql_{}_related_tables", self.base_table), Span::call_site());
-        let related_pks_macro_name = Ident::new(&format!("tql_{}_related_pks", self.base_table), self.base_field.span());
-        let base_table = &self.base_table;
-        let base_field = self.base_field.to_sql(&mut 1);
+        let join_condition_macro_name = Ident::new(&format!("tql_{}_join_condition", self.base_table),
+            self.base_field.span());
+        let join_type_macro_name = Ident::new(&format!("tql_{}_join_type", self.base_table), Span::call_site());
         let base_field_ident = &self.base_field;
         let related_table_name = quote_spanned! { Span::call_site() =>
             #related_table_macro_name!(#base_field_ident)
         };
         quote! {
-            " INNER JOIN ", #related_table_name, " ON ", #base_table, ".", #base_field, " = ",
-                    #related_table_name, ".", #related_pks_macro_name!(#base_field_ident)
+            #join_type_macro_name!(#base_field_ident), #related_table_name, " ON ", #join_condition_macro_name!(#base_field_ident)
         }
     }
 }
 
-fn sep_by<I: Iterator<Item=TokenStream>>(elements: I, sep: &str) -> TokenStream {
+pub fn sep_by<I: Iterator<Item=TokenStream>>(elements: I, sep: &str) -> TokenStream {
     let mut elements: Vec<_> = elements.collect();
     if let Some(last_element) = elements.pop() {
         let elements = elements.iter()
@@ -253,6 +302,16 @@ fn sep_by<I: Iterator<Item=TokenStream>>(elements: I, sep: &str) -> TokenStream
     }
 }
 
+/// Join the `parts` with the `separator` between each of them.
+fn join_tokens(parts: Vec<TokenStream>, separator: &TokenStream) -> TokenStream {
+    let mut parts = parts.into_iter();
+    let first_part = parts.next().unwrap_or_else(|| quote! { "" });
+    let separators = iter::repeat(separator);
+    quote! {
+        #first_part #(, #separators, #parts)*
+    }
+}
+
 fn joins_to_check(joins: &[Join]) -> TokenStream {
     let checks = joins.iter().map(|join| join.to_check());
     quote! {
@@ -281,12 +340,54 @@ impl ToSql for String {
     }
 }
 
+/// Get the tokens for the SQL name of the table.
+fn table_name_tokens(table: &str) -> TokenStream {
+    let macro_name = Ident::new(&format!("tql_{}_table_name", table), Span::call_site());
+    quote! {
+        #macro_name!()
+    }
+}
+
+/// Get the tokens for the SQL name of the column of the `field`.
+fn column_tokens(table: &str, field: &Ident) -> TokenStream {
+    let macro_name = Ident::new(&format!("tql_{}_column_name", table), Span::call_site());
+    quote! {
+        #macro_name!(#field)
+    }
+}
+
+/// Get the tokens for the SQL name of the column of the assigned field, which can be in an embedded struct.
+fn assignment_column_tokens(table: &str, assignment: &Assignment) -> TokenStream {
+    let field = assignment.identifier.as_ref().expect("Assignment identifier");
+    match assignment.embedded_field {
+        Some(ref embedded_field) => {
+            let macro_name = Ident::new(&format!("tql_{}_column_name", table), Span::call_site());
+            quote! {
+                #macro_name!(#field.#embedded_field)
+            }
+        },
+        None => column_tokens(table, field),
+    }
+}
+
+/// Get the tokens for the SQL name of the column of the `field` prefixed by its table name.
+fn qualified_column_tokens(table: &str, field: &Ident) -> TokenStream {
+    let table_name = table_name_tokens(table);
+    let column = column_tokens(table, field);
+    quote! {
+        #table_name, ".", #column
+    }
+}
+
+fn columns_to_tokens(table: &str, fields: &[Ident]) -> TokenStream {
+    sep_by(fields.iter().map(|field| column_tokens(table, field)), ", ")
+}
+
 /// Convert a whole `Query` to SQL.
 impl Query {
     pub fn to_tokens(&self) -> TokenStream {
         match *self {
-            Query::Aggregate { ref aggregates, ref aggregate_filter, ref filter, ref groups, ref joins, ref table } => {
-                let where_clause = filter_to_where_clause(filter);
+            Query::Aggregate { ref aggregates, ref aggregate_filter, deleted, ref filter, ref groups, ref joins, ref table } => {
                 let group_clause =
                     if !groups.is_empty() {
                         " GROUP BY "
@@ -301,17 +402,24 @@ impl Query {
                     else {
                         " HAVING "
                     };
-                let aggregates = aggregates.to_sql(&mut 1);
+                let aggregates = aggregates_to_tokens(aggregates, table);
                 let check_joins = joins_to_check(&joins);
                 let joins = joins_to_tokens(&joins);
                 let index = &mut 1;
-                let filter = filter.to_tokens(index);
-                let groups = groups.to_sql(&mut 1);
-                let aggregate_filter = aggregate_filter.to_sql(index);
+                let filter = where_clause_tokens(table, filter, filter.to_tokens(table, index), deleted);
+                let groups =
+                    if !groups.is_empty() {
+                        columns_to_tokens(table, groups)
+                    }
+                    else {
+                        quote! { "" }
+                    };
+                let aggregate_filter = aggregate_filter.to_tokens(table, index);
+                let table = table_name_tokens(table);
                 quote! {{
                     #check_joins
-                    concat!("SELECT ", #aggregates, " FROM ", #table, #joins, #where_clause, #filter, #group_clause,
-                            #groups, #having_clause, #aggregate_filter)
+                    concat!("SELECT ", #aggregates, " FROM ", #table, #joins, #filter, #group_clause, #groups,
+                            #having_clause, #aggregate_filter)
                 }}
             },
             Query::CreateTable { ref table } => {
@@ -320,30 +428,103 @@ impl Query {
                     #macro_name!()
                 }
             },
-            Query::Delete { ref filter, ref table, use_pk: _use_pk } => {
-                let where_clause = filter_to_where_clause(filter);
-                let filter = filter.to_tokens(&mut 1);
-                quote! {
-                    concat!("DELETE FROM ", #table, #where_clause, #filter)
+            Query::Delete { ref filter, hard, ref table, use_pk: _use_pk } => {
+                // NOTE: the rows of a table with soft_delete are updated by delete(), unless they are already deleted.
+                let macro_name = Ident::new(&format!("tql_{}_soft_delete", table), Span::call_site());
+                let filter_tokens = filter.to_tokens(table, &mut 1);
+                if hard {
+                    let where_clause = filter_to_where_clause(filter);
+                    quote! {
+                        concat!(#macro_name!(hard_delete), #where_clause, #filter_tokens)
+                    }
+                }
+                else {
+                    let filter = where_clause_tokens(table, filter, filter_tokens, Deleted::Exclude);
+                    quote! {
+                        concat!(#macro_name!(delete), #filter)
+                    }
                 }
             },
             Query::Drop { ref table } => {
-                string_token(format!("DROP TABLE {table}", table = table).as_str())
+                let table = table_name_tokens(table);
+                quote! {
+                    concat!("DROP TABLE ", #table)
+                }
             },
             Query::Insert { ref assignments, ref table } => {
-                let fields: Vec<_> = assignments.iter().map(|assign|
-                    assign.identifier.clone().expect("Assignment identifier").to_sql(&mut 1)).collect();
+                let fields = sep_by(assignments.iter().map(|assign| assignment_column_tokens(table, assign)), ", ");
                 let index = &mut 1;
                 let values: Vec<_> = assignments.iter().map(|assign|
                     assign.value.to_sql(index)
                 ).collect();
+                let values = values.to_sql(index);
+                // NOTE: a primary key generated by tql is added after the inserted fields, followed by
+                // the current time of the fields with auto_now or auto_now_add.
+                let macro_name = Ident::new(&format!("tql_{}_generated_primary_key", table), Span::call_site());
+                let auto_now_macro_name = Ident::new(&format!("tql_{}_auto_now", table), Span::call_site());
+                let idents = &get_insert_idents(self).unwrap_or_default();
+                let generated_value = format!(", ${}", index);
+                let fields = quote! {
+                    #fields, #macro_name!(column [#(#idents),*]), #auto_now_macro_name!(column [#(#idents),*])
+                };
+                let values = quote! {
+                    #values, #macro_name!(value #generated_value [#(#idents),*]),
+                    #auto_now_macro_name!(value [#(#idents),*])
+                };
                 // Add the SQL code to get the inserted primary key.
                 // TODO: what to do when there is no primary key?
                 let backend = create_sql_backend();
-                backend.insert_query(table, &fields, &values)
+                backend.insert_query(table, fields, values)
             },
-            Query::Select { ref filter, get: _get, ref joins, ref limit, ref order, ref table, use_pk: _use_pk } => {
+            Query::Relation { ref action, ref field, ref filter, ref table } => {
+                let macro_name = Ident::new(&format!("tql_{}_many_to_many", table), Span::call_site());
+                // NOTE: the link table of a through model is not modified here.
+                let link_table = quote! { #macro_name!(#field, modified_table) };
+                let source = quote! { #macro_name!(#field, source) };
+                let target = quote! { #macro_name!(#field, target) };
+                let related_table = quote! { #macro_name!(#field, related_table) };
+                // NOTE: the related table is aliased since it can be the same table for a
+                // self-referential field.
+                let related_alias = quote! { #macro_name!(#field, related_alias) };
+                let related_primary_key = quote! { #macro_name!(#field, related_primary_key) };
+                let primary_key = FilterValue::PrimaryKey(table.clone(), None).to_tokens(table, &mut 1);
                 let where_clause = filter_to_where_clause(filter);
+                let index = &mut 1;
+                let filter = filter.to_tokens(table, index);
+                let table = table_name_tokens(table);
+                // The selected items.
+                let items = quote! {
+                    "SELECT ", #primary_key, " FROM ", #table, #where_clause, #filter
+                };
+                match *action {
+                    RelationAction::Add(ref related_item) => {
+                        let related_item = related_item.to_sql(index);
+                        quote! {
+                            concat!("INSERT INTO ", #link_table, " (", #source, ", ", #target, ") SELECT ", #primary_key,
+                                    ", ", #related_alias, ".", #related_primary_key, " FROM ", #table, ", ",
+                                    #related_table, " AS ", #related_alias, " WHERE ", #primary_key, " IN (", #items,
+                                    ") AND ", #related_alias, ".", #related_primary_key, " = ", #related_item)
+                        }
+                    },
+                    RelationAction::Clear => quote! {
+                        concat!("DELETE FROM ", #link_table, " WHERE ", #source, " IN (", #items, ")")
+                    },
+                    RelationAction::Create => quote! {
+                        #macro_name!(#field, create)
+                    },
+                    RelationAction::Drop => quote! {
+                        #macro_name!(#field, drop)
+                    },
+                    RelationAction::Remove(ref related_item) => {
+                        let related_item = related_item.to_sql(index);
+                        quote! {
+                            concat!("DELETE FROM ", #link_table, " WHERE ", #source, " IN (", #items, ") AND ",
+                                    #target, " = ", #related_item)
+                        }
+                    },
+                }
+            },
+            Query::Select { deleted, ref filter, get: _get, ref joins, ref limit, ref order, ref table, use_pk: _use_pk } => {
                 let order_clause =
                     if has_order_clauses(order) {
                         " ORDER BY "
@@ -356,22 +537,31 @@ impl Query {
                 let check_joins = joins_to_check(&joins);
                 let joins = joins_to_tokens(&joins);
                 let index = &mut 1;
-                let filter = filter.to_tokens(index);
-                let order = order.to_sql(&mut 1);
+                let filter = where_clause_tokens(table, filter, filter.to_tokens(table, index), deleted);
+                let order = orders_to_tokens(order, table);
                 let limit = limit.to_sql(&mut 1);
+                let table = table_name_tokens(table);
                 quote_spanned! { Span::call_site() => {
                     #check_joins
-                    concat!("SELECT ", #macro_name!() #joined_fields, " FROM ", #table, #joins, #where_clause, #filter,
-                        #order_clause, #order, #limit)
+                    concat!("SELECT ", #macro_name!() #joined_fields, " FROM ", #table, #joins, #filter, #order_clause,
+                        #order, #limit)
                 }}
             },
             Query::Update { ref assignments, ref filter, ref table, use_pk: _use_pk } => {
                 let where_clause = filter_to_where_clause(filter);
+                // NOTE: the fields with auto_now are set to the current time, unless they are assigned.
+                let macro_name = Ident::new(&format!("tql_{}_auto_now", table), Span::call_site());
+                let idents = assignments.iter()
+                    .filter_map(|assignment| assignment.identifier.as_ref());
+                let auto_now = quote! {
+                    #macro_name!(update [#(#idents),*])
+                };
                 let index = &mut 1;
-                let assignments = assignments.to_sql(index);
-                let filter = filter.to_tokens(index);
+                let assignments = assignments_to_tokens(assignments, table, index);
+                let filter = filter.to_tokens(table, index);
+                let table = table_name_tokens(table);
                 quote! {
-                    concat!("UPDATE ", #table, " SET ", #assignments, #where_clause, #filter)
+                    concat!("UPDATE ", #table, " SET ", #assignments, #auto_now, #where_clause, #filter)
                 }
             },
         }
@@ -379,21 +569,22 @@ impl Query {
 }
 
 impl Filter {
-    fn to_tokens(&self, index: &mut usize) -> TokenStream {
-        let operand1 = self.operand1.to_tokens();
+    fn to_tokens(&self, table: &str, index: &mut usize) -> TokenStream {
+        let operand1 = self.operand1.to_tokens(table, index);
         let operator = self.operator.to_sql(index);
         let operand2 = self.operand2.to_sql(index);
+        let end = self.operand1.end_tokens();
         quote! {
-            #operand1, " ", #operator, " ", #operand2
+            #operand1, " ", #operator, " ", #operand2 #end
         }
     }
 }
 
 impl Filters {
-    fn to_tokens(&self, index: &mut usize) -> TokenStream {
-        let operand1 = self.operand1.to_tokens(index);
+    fn to_tokens(&self, table: &str, index: &mut usize) -> TokenStream {
+        let operand1 = self.operand1.to_tokens(table, index);
         let operator = self.operator.to_sql(index);
-        let operand2 = self.operand2.to_tokens(index);
+        let operand2 = self.operand2.to_tokens(table, index);
         quote! {
             #operand1, " ", #operator, " ", #operand2
         }
@@ -401,44 +592,126 @@ impl Filters {
 }
 
 impl FilterValue {
-    fn to_tokens(&self) -> TokenStream {
-        let sql =
-            match *self {
-                FilterValue::Identifier(ref table, ref identifier) => format!("{}.{}", table, identifier.to_sql(&mut 1)),
-                FilterValue::MethodCall(MethodCall { ref arguments, ref object_name, ref method_name, ..  }) => {
-                    let methods = methods_singleton();
-                    if let Some(method) = methods.get(&method_name.to_string()) {
+    /// Get the tokens ending the condition on this value.
+    /// The condition on a field of the items related by a `ManyToMany` field is in a subquery.
+    fn end_tokens(&self) -> TokenStream {
+        match *self {
+            FilterValue::RelatedField(ref table, ref relation, _) => {
+                let macro_name = Ident::new(&format!("tql_{}_embedded_field", table), Span::call_site());
+                quote! { , #macro_name!(#relation end) }
+            },
+            _ => quote! {},
+        }
+    }
+
+    fn to_tokens(&self, table: &str, index: &mut usize) -> TokenStream {
+        match *self {
+            FilterValue::Identifier(ref table, ref identifier) => qualified_column_tokens(table, identifier),
+            FilterValue::MethodCall(MethodCall { ref arguments, ref object_name, ref method_name, ..  }) => {
+                let methods = methods_singleton();
+                if let Some(methods) = methods.get(&method_name.to_string()) {
+                    // NOTE: the arguments are converted first, so that the query parameters are numbered in the
+                    // same order as they are sent.
+                    let arguments: Vec<_> = arguments.iter()
+                        .map(|argument| argument.to_sql(index))
+                        .collect();
+                    let column = column_tokens(table, object_name);
+                    let template_tokens = |method: &SqlMethodTypes| {
                         // In the template, $0 represents the object identifier and $1, $2, ... the
                         // arguments.
-                        let mut sql = method.template.as_ref().map(|string| string.as_str()).unwrap_or_default()
-                            // NOTE: it's safe to use unwrap_or_default() because we check if the method exists for the
-                            // backend in the method analyzer.
-                            .replace("$0", &object_name.to_string());
-                        let mut index = 1;
-                        for argument in arguments {
-                            sql = sql.replace(&format!("${}", index), &argument.to_sql(&mut 1));
-                            index += 1;
-                        }
-                        sql
-                    }
-                    else {
-                        // NOTE: type checking will disallow this code to be executed.
-                        String::new()
+                        let template = method.template.as_ref().map(|string| string.as_str()).unwrap_or_default();
+                        // NOTE: it's safe to use unwrap_or_default() because we check if the method exists for the
+                        // backend in the method analyzer.
+                        let parts = template.split("$0")
+                            .map(|part| string_token(&replace_arguments(part, &arguments)))
+                            .collect();
+                        join_tokens(parts, &column)
+                    };
+                    let array_method = methods.iter().find(|method| matches!(method.object_type, Type::Array(_)));
+                    let other_method = methods.iter().find(|method| !matches!(method.object_type, Type::Array(_)));
+                    match (array_method, other_method) {
+                        // NOTE: only the table knows if the field is an array, so its macro chooses the template
+                        // of a method existing on both the arrays and another type (like len()).
+                        (Some(array_method), Some(other_method)) if array_method.template.is_some() => {
+                            let macro_name = Ident::new(&format!("tql_{}_array_field", table), Span::call_site());
+                            let array_tokens = template_tokens(array_method);
+                            let other_tokens = template_tokens(other_method);
+                            quote! {
+                                #macro_name!(#object_name [#array_tokens] [#other_tokens])
+                            }
+                        },
+                        (_, Some(method)) | (Some(method), None) => template_tokens(method),
+                        (None, None) => quote! { "" },
                     }
-                },
-                FilterValue::None => unreachable!("FilterValue::None in FilterValue::to_sql()"),
-                FilterValue::PrimaryKey(ref table) => {
-                    let macro_name = Ident::new(&format!("tql_{}_primary_key_field", table), Span::call_site());
-                    return quote! {
-                        #table, ".", #macro_name!()
+                }
+                else {
+                    // NOTE: type checking will disallow this code to be executed.
+                    quote! { "" }
+                }
+            },
+            FilterValue::None => unreachable!("FilterValue::None in FilterValue::to_sql()"),
+            FilterValue::PrimaryKey(ref table, index) => {
+                let macro_name = Ident::new(&format!("tql_{}_primary_key_field", table), Span::call_site());
+                let table = table_name_tokens(table);
+                let column =
+                    match index {
+                        Some(index) => {
+                            let index = Literal::usize_unsuffixed(index);
+                            quote! { #index }
+                        },
+                        None => quote! { single },
                     };
-                },
-            };
-        let expr = string_literal(&sql);
-        quote! {
-            #expr
+                quote! {
+                    #table, ".", #macro_name!(#column)
+                }
+            },
+            FilterValue::RelatedField(ref table, ref relation, ref identifier) => {
+                let macro_name = Ident::new(&format!("tql_{}_many_to_many", table), Span::call_site());
+                let link_table = quote! { #macro_name!(#relation, table) };
+                let related_table = quote! { #macro_name!(#relation, related_table) };
+                let primary_key = FilterValue::PrimaryKey(table.clone(), None).to_tokens(table, &mut 1);
+                // NOTE: only the table knows if the field is embedded, so its macro chooses between the column of
+                // the embedded struct and the subquery on the related items.
+                let embedded_macro_name = Ident::new(&format!("tql_{}_embedded_field", table), Span::call_site());
+                quote! {
+                    #embedded_macro_name!(#relation.#identifier [
+                        #primary_key, " IN (SELECT ", #link_table, ".", #macro_name!(#relation, source), " FROM ",
+                            #link_table, " INNER JOIN ", #related_table, " ON ", #link_table, ".",
+                            #macro_name!(#relation, target), " = ", #related_table, ".",
+                            #macro_name!(#relation, related_primary_key), " WHERE ", #related_table, ".",
+                            #macro_name!(#relation, related_column, #identifier)
+                    ])
+                }
+            },
+        }
+    }
+}
+
+/// Replace the $1, $2, ... placeholders of a method template by the arguments.
+/// The replacement is done in one pass since an argument can itself be a query parameter like $2.
+fn replace_arguments(template: &str, arguments: &[String]) -> String {
+    let mut sql = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(character) = chars.next() {
+        let mut number = String::new();
+        if character == '$' {
+            while let Some(&digit) = chars.peek() {
+                if !digit.is_ascii_digit() {
+                    break;
+                }
+                number.push(digit);
+                chars.next();
+            }
+        }
+        match number.parse::<usize>().ok().and_then(|number| arguments.get(number.wrapping_sub(1))) {
+            Some(argument) => sql.push_str(argument),
+            None => {
+                sql.push(character);
+                sql.push_str(&number);
+            },
         }
     }
+    sql
 }
 
 /// Convert a literal expression to its SQL representation.
@@ -490,24 +763,39 @@ fn escape(string: String) -> String {
 pub fn type_to_sql(typ: &Type, nullable: bool) -> TokenStream {
     let sql_type =
         match *typ {
-            Type::Bool => "BOOLEAN",
-            Type::ByteString => "BYTEA",
-            Type::I8 | Type::Char => "CHARACTER(1)",
-            Type::Custom(ref related_table_name) => {
-                let pk_macro_name = Ident::new(&format!("tql_{}_primary_key_field", related_table_name),
-                    Span::call_site());
+            Type::Array(ref typ) => {
+                // NOTE: the arrays are only supported with PostgreSQL, which is checked by get_struct_fields().
+                let sql = type_to_sql(typ, true);
+                let not_null = if nullable { "" } else { " NOT NULL" };
                 return quote! {
-                    "INTEGER REFERENCES ", #related_table_name, "(", #pk_macro_name!(), ") NOT NULL"
+                    #sql, "[]", #not_null
                 };
-                // NOTE: if the field type is not an SQL table, an error is thrown.
             },
+            Type::Bool => "BOOLEAN",
+            Type::ByteString => "BYTEA",
+            Type::I8 | Type::Char => "CHARACTER(1)",
+            Type::Custom(ref related_table_name) => return references_to_sql(related_table_name, nullable, ""),
+            Type::Decimal => "NUMERIC",
+            #[cfg(feature = "postgres")]
+            Type::Duration => "INTERVAL",
+            #[cfg(not(feature = "postgres"))]
+            Type::Duration => "BIGINT", // NOTE: the number of microseconds.
             Type::F32 => "REAL",
             Type::F64 => "DOUBLE PRECISION",
             Type::Generic => "", // TODO: document why this is empty.
             Type::I16 => "SMALLINT",
             Type::I32 => "INTEGER",
             Type::I64 => "BIGINT",
+            #[cfg(feature = "postgres")]
+            Type::IpAddr => "INET",
+            #[cfg(not(feature = "postgres"))]
+            Type::IpAddr => "TEXT",
+            #[cfg(feature = "postgres")]
+            Type::Json => "JSONB",
+            #[cfg(not(feature = "postgres"))]
+            Type::Json => "TEXT",
             Type::LocalDateTime => "TIMESTAMP WITH TIME ZONE",
+            Type::ManyToMany(_) => "", // NOTE: the related items are stored in a link table.
             Type::NaiveDate => "DATE",
             Type::NaiveDateTime => "TIMESTAMP",
             Type::NaiveTime => "TIME",
@@ -517,15 +805,33 @@ pub fn type_to_sql(typ: &Type, nullable: bool) -> TokenStream {
                     #sql
                 };
             },
-            #[cfg(feature = "rusqlite")]
-            Type::Serial => "INTEGER PRIMARY KEY",
-            #[cfg(feature = "postgres")]
-            Type::Serial => "SERIAL PRIMARY KEY",
-            #[cfg(not(any(feature = "rusqlite", feature = "postgres")))]
-            Type::Serial => unreachable!("Enable one of the following features: sqlite, pg"),
+            Type::PrimaryKey(ref typ) => {
+                let sql = primary_key_type_to_sql(typ);
+                return quote! {
+                    #sql, " NOT NULL"
+                };
+            },
             Type::String => "CHARACTER VARYING",
+            // NOTE: the values greater than i64::MAX cannot be stored in a u64 column.
+            Type::U16 => "INTEGER",
+            Type::U32 | Type::U64 => "BIGINT",
             Type::UnsupportedType(_) => "", // TODO: should panic. TODO: document why.
+            Type::UserDefined(ref typ) => {
+                let macro_name = Ident::new(&format!("tql_{}_sql_type", typ), Span::call_site());
+                if nullable {
+                    return quote! {
+                        #macro_name!()
+                    };
+                }
+                return quote! {
+                    #macro_name!(), " NOT NULL"
+                };
+            },
             Type::UtcDateTime => "TIMESTAMP WITH TIME ZONE",
+            #[cfg(feature = "postgres")]
+            Type::Uuid => "UUID",
+            #[cfg(not(feature = "postgres"))]
+            Type::Uuid => "BLOB",
         };
 
     let expr = string_literal(sql_type);
@@ -541,12 +847,87 @@ pub fn type_to_sql(typ: &Type, nullable: bool) -> TokenStream {
     }
 }
 
-impl ToSql for [Order] {
-    fn to_sql(&self, index: &mut usize) -> String {
-        self.iter().map(|order| order.to_sql(index)).collect::<Vec<_>>().join(", ")
+/// Convert the type of a column to SQL, with the referential actions of a `ForeignKey` column, the
+/// precision and scale of a `Decimal` column and the maximum length of a `String` column.
+pub fn column_type_to_sql(typ: &Type, attributes: &FieldAttributes) -> TokenStream {
+    let (typ, nullable) =
+        match *typ {
+            Type::Nullable(ref typ) => (&**typ, true),
+            _ => (typ, false),
+        };
+    if let Type::Custom(ref related_table_name) = *typ {
+        return references_to_sql(related_table_name, nullable, &referential_actions_to_sql(attributes));
+    }
+    if let (&Type::Decimal, Some(precision)) = (typ, attributes.precision) {
+        let sql_type = format!("NUMERIC({},{})", precision, attributes.scale.unwrap_or_default());
+        let not_null = if nullable { "" } else { " NOT NULL" };
+        return quote! {
+            #sql_type, #not_null
+        };
+    }
+    if let (&Type::String, Some(max_length)) = (typ, attributes.max_length) {
+        let sql_type = format!("VARCHAR({})", max_length);
+        let not_null = if nullable { "" } else { " NOT NULL" };
+        return quote! {
+            #sql_type, #not_null
+        };
+    }
+    type_to_sql(typ, nullable)
+}
+
+/// Convert the type of a `ForeignKey` column to SQL.
+fn references_to_sql(related_table_name: &str, nullable: bool, actions: &str) -> TokenStream {
+    let pk_macro_name = Ident::new(&format!("tql_{}_primary_key_field", related_table_name), Span::call_site());
+    let related_table_name = table_name_tokens(related_table_name);
+    let not_null = if nullable { "" } else { " NOT NULL" };
+    // NOTE: the type of the column is the type of the primary key of the related table.
+    // NOTE: if the field type is not an SQL table, an error is thrown.
+    quote! {
+        #pk_macro_name!(sql_type), " REFERENCES ", #related_table_name, "(", #pk_macro_name!(single), ")", #actions,
+            #not_null
     }
 }
 
+/// Convert the `on_delete` and `on_update` attributes of a `ForeignKey` to SQL.
+fn referential_actions_to_sql(attributes: &FieldAttributes) -> String {
+    let mut sql = String::new();
+    if let Some(action) = attributes.on_delete {
+        sql.push_str(&format!(" ON DELETE {}", action.to_sql()));
+    }
+    if let Some(action) = attributes.on_update {
+        sql.push_str(&format!(" ON UPDATE {}", action.to_sql()));
+    }
+    sql
+}
+
+/// Convert the inner type of a primary key to SQL.
+/// An integer primary key is generated by the database.
+fn primary_key_type_to_sql(typ: &Type) -> TokenStream {
+    match *typ {
+        #[cfg(feature = "rusqlite")]
+        Type::I32 | Type::I64 => quote! { "INTEGER PRIMARY KEY" },
+        #[cfg(feature = "postgres")]
+        Type::I32 => quote! { "SERIAL PRIMARY KEY" },
+        #[cfg(feature = "postgres")]
+        Type::I64 => quote! { "BIGSERIAL PRIMARY KEY" },
+        #[cfg(not(any(feature = "rusqlite", feature = "postgres")))]
+        Type::I32 | Type::I64 => unreachable!("Enable one of the following features: sqlite, pg"),
+        _ => {
+            let sql = type_to_sql(typ, true);
+            quote! {
+                #sql, " PRIMARY KEY"
+            }
+        },
+    }
+}
+
+fn orders_to_tokens(orders: &[Order], table: &str) -> TokenStream {
+    let orders = orders.iter()
+        .filter(|order| !matches!(**order, Order::NoOrder))
+        .map(|order| order.to_tokens(table));
+    sep_by(orders, ", ")
+}
+
 impl ToSql for LogicalOperator {
     fn to_sql(&self, _index: &mut usize) -> String {
         match *self {
@@ -557,12 +938,17 @@ impl ToSql for LogicalOperator {
     }
 }
 
-impl ToSql for Order {
-    fn to_sql(&self, index: &mut usize) -> String {
+impl Order {
+    fn to_tokens(&self, table: &str) -> TokenStream {
         match *self {
-            Order::Ascending(ref field) => field.to_sql(index),
-            Order::Descending(ref field) => field.to_sql(index) + " DESC",
-            Order::NoOrder => String::new(),
+            Order::Ascending(ref field) => column_tokens(table, field),
+            Order::Descending(ref field) => {
+                let column = column_tokens(table, field);
+                quote! {
+                    #column, " DESC"
+                }
+            },
+            Order::NoOrder => quote! { "" },
         }
     }
 }
@@ -604,16 +990,63 @@ impl ToSql for RelationalOperator {
     }
 }
 
-pub fn fields_to_sql(fields: &[TypedField]) -> TokenStream {
-    let fields = fields.iter()
-        .map(|field| {
-             let ident = field.identifier.to_sql(&mut 1);
-             let typ = &field.typ;
-             quote! {
-                 #ident, " ", #typ
-             }
-        });
-    sep_by(fields, ", ")
+/// Convert the constraints from the attributes of a field to SQL.
+pub fn constraints_to_sql(attributes: &FieldAttributes) -> TokenStream {
+    let mut sql = String::new();
+    if attributes.unique {
+        sql.push_str(" UNIQUE");
+    }
+    if let Some(ref check) = attributes.check {
+        sql.push_str(&format!(" CHECK ({})", check));
+    }
+    string_token(&sql)
+}
+
+/// Convert the constraints spanning multiple columns (`PRIMARY KEY` and `UNIQUE`) to SQL.
+pub fn table_constraints_to_sql(primary_key_columns: &[String], unique_columns: &[Vec<String>]) -> TokenStream {
+    let mut sql = String::new();
+    if !primary_key_columns.is_empty() {
+        sql.push_str(&format!(", PRIMARY KEY ({})", primary_key_columns.join(", ")));
+    }
+    for columns in unique_columns {
+        sql.push_str(&format!(", UNIQUE ({})", columns.join(", ")));
+    }
+    string_token(&sql)
+}
+
+/// Convert a foreign key stored in multiple `columns` to SQL.
+pub fn foreign_key_to_sql(columns: &[String], related_table_name: &str, attributes: &FieldAttributes) -> TokenStream {
+    let pk_macro_name = Ident::new(&format!("tql_{}_primary_key_field", related_table_name), Span::call_site());
+    let columns = format!(", FOREIGN KEY ({}) REFERENCES ", columns.join(", "));
+    let related_table_name = table_name_tokens(related_table_name);
+    let actions = referential_actions_to_sql(attributes);
+    quote! {
+        #columns, #related_table_name, "(", #pk_macro_name!(), ")", #actions
+    }
+}
+
+/// Get the tokens of the where clause of the `filter`, which also selects the rows of a table with
+/// `#[tql(soft_delete)]` depending on whether they are `deleted`.
+fn where_clause_tokens(table: &str, filter: &FilterExpression, filter_tokens: TokenStream, deleted: Deleted)
+    -> TokenStream
+{
+    let macro_name = Ident::new(&format!("tql_{}_soft_delete", table), Span::call_site());
+    let deleted =
+        match deleted {
+            Deleted::Exclude => quote! { exclude },
+            Deleted::Include => quote! { include },
+            Deleted::Only => quote! { only },
+        };
+    if let FilterExpression::NoFilters = *filter {
+        quote! {
+            #macro_name!(no_filter #deleted)
+        }
+    }
+    else {
+        quote! {
+            #macro_name!(filter_start #deleted), #filter_tokens, #macro_name!(filter_end #deleted)
+        }
+    }
 }
 
 /// Convert a `FilterExpression` to either " WHERE " or the empty string if there are no filters.
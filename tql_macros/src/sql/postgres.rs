@@ -25,7 +25,8 @@ use proc_macro2::{Span,TokenStream};
 use syn::Ident;
 
 use ast::Aggregate;
-use sql::{SqlBackend, ToSql, string_token};
+use sql::{SqlBackend, SqlEnum, ToSql, column_tokens, table_name_tokens, variants_to_sql};
+use types::Type;
 
 pub struct PostgresSqlBackend {}
 
@@ -33,26 +34,43 @@ pub fn create_sql_backend() -> PostgresSqlBackend {
     PostgresSqlBackend { }
 }
 
-impl ToSql for Aggregate {
-    fn to_sql(&self, index: &mut usize) -> String {
+impl Aggregate {
+    pub fn to_tokens(&self, table: &str) -> TokenStream {
         // TODO: do not hard-code the type.
-        "CAST(".to_string() + &self.sql_function.to_sql(index) + "(" +
-            &self.field.clone().expect("Aggregate field").to_sql(index) + ") AS DOUBLE PRECISION)"
+        let function = self.sql_function.to_sql(&mut 1);
+        let column = column_tokens(table, self.field.as_ref().expect("Aggregate field"));
+        quote! {
+            "CAST(", #function, "(", #column, ") AS DOUBLE PRECISION)"
+        }
     }
 }
 
 impl SqlBackend for PostgresSqlBackend {
-    fn insert_query(&self, table: &str, fields: &[String], values: &[String]) -> TokenStream {
-        let query_start =
-            format!("INSERT INTO {table}({fields}) VALUES({values}) RETURNING ",
-            table = table,
-            fields = fields.to_sql(&mut 1),
-            values = values.to_sql(&mut 1),
-            );
-        let query_start = string_token(&query_start);
+    fn current_timestamp(&self, typ: &Type) -> Option<&'static str> {
+        match *typ {
+            Type::NaiveDateTime => Some("(CURRENT_TIMESTAMP AT TIME ZONE 'UTC')"),
+            Type::UtcDateTime => Some("CURRENT_TIMESTAMP"),
+            _ => None,
+        }
+    }
+
+    fn enum_to_sql(&self, name: &str, variants: &[String]) -> SqlEnum {
+        // NOTE: PostgreSQL has no CREATE TYPE IF NOT EXISTS, so ignore the error when multiple
+        // tables use the same enum.
+        let create_query = format!("DO $$ BEGIN CREATE TYPE {} AS ENUM ({}); \
+                                    EXCEPTION WHEN duplicate_object THEN NULL; END $$", name, variants_to_sql(variants));
+        SqlEnum {
+            column_type: name.to_string(),
+            check: None,
+            create_query: Some(create_query),
+        }
+    }
+
+    fn insert_query(&self, table: &str, fields: TokenStream, values: TokenStream) -> TokenStream {
         let macro_name = Ident::new(format!("tql_{}_primary_key_field", table).as_str(), Span::call_site());
+        let table = table_name_tokens(table);
         quote! {
-            concat!(#query_start, #macro_name!())
+            concat!("INSERT INTO ", #table, "(", #fields, ") VALUES(", #values, ") RETURNING ", #macro_name!())
         }
     }
 }
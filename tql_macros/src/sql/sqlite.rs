@@ -24,7 +24,8 @@
 use proc_macro2::TokenStream;
 
 use ast::Aggregate;
-use sql::{SqlBackend, ToSql};
+use sql::{SqlBackend, SqlEnum, ToSql, column_tokens, table_name_tokens, variants_to_sql};
+use types::Type;
 
 pub struct SqliteSqlBackend {}
 
@@ -32,22 +33,38 @@ pub fn create_sql_backend() -> SqliteSqlBackend {
     SqliteSqlBackend { }
 }
 
-impl ToSql for Aggregate {
-    fn to_sql(&self, index: &mut usize) -> String {
-        self.sql_function.to_sql(index) + "(" + &self.field.clone().expect("Aggregate field").to_sql(index) + ")"
+impl Aggregate {
+    pub fn to_tokens(&self, table: &str) -> TokenStream {
+        let function = self.sql_function.to_sql(&mut 1);
+        let column = column_tokens(table, self.field.as_ref().expect("Aggregate field"));
+        quote! {
+            #function, "(", #column, ")"
+        }
     }
 }
 
 impl SqlBackend for SqliteSqlBackend {
-    fn insert_query(&self, table: &str, fields: &[String], values: &[String]) -> TokenStream {
-        let query =
-            format!("INSERT INTO {table}({fields}) VALUES({values})",
-            table = table,
-            fields = fields.to_sql(&mut 1),
-            values = values.to_sql(&mut 1),
-            );
+    // NOTE: the current time is formatted like the dates sent by rusqlite.
+    fn current_timestamp(&self, typ: &Type) -> Option<&'static str> {
+        match *typ {
+            Type::NaiveDateTime => Some("strftime('%Y-%m-%dT%H:%M:%f', 'now')"),
+            Type::UtcDateTime => Some("strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')"),
+            _ => None,
+        }
+    }
+
+    fn enum_to_sql(&self, _name: &str, variants: &[String]) -> SqlEnum {
+        SqlEnum {
+            column_type: "TEXT".to_string(),
+            check: Some(format!("IN ({})", variants_to_sql(variants))),
+            create_query: None,
+        }
+    }
+
+    fn insert_query(&self, table: &str, fields: TokenStream, values: TokenStream) -> TokenStream {
+        let table = table_name_tokens(table);
         quote! {
-            concat!(#query)
+            concat!("INSERT INTO ", #table, "(", #fields, ") VALUES(", #values, ")")
         }
     }
 }
@@ -10,6 +10,7 @@ use ast::{
     Aggregate,
     Assignment,
     AssignmentOperator,
+    Filter,
     FilterExpression,
     FilterValue,
     Groups,
@@ -19,6 +20,7 @@ use ast::{
     MethodCall,
     Order,
     Query,
+    RelationAction,
     RelationalOperator,
 };
 use parser::MethodCalls;
@@ -28,12 +30,29 @@ pub fn generate_macro_patterns(query: &Query, calls: &MethodCalls) -> TokenStrea
     let mut dummy_count = 0;
     let mut args = vec![];
     let table_name = calls.name.clone().expect("table name");
+    let generic_arguments = &calls.generic_arguments;
     let mut methods = quote! {};
-    for call in &calls.calls {
+    for (index, call) in calls.calls.iter().enumerate() {
+        if let Some((ref relation, relation_index)) = calls.relation {
+            if index == relation_index {
+                methods = quote! {
+                    #methods
+                    . #relation
+                };
+            }
+        }
         let name = &call.name;
         let args =
             match name.to_string().as_str() {
-                "all" | "create" | "delete" | "drop" => quote! {},
+                "all" | "clear" | "create" | "delete" | "drop" | "hard_delete" | "only_deleted" | "with_deleted" =>
+                    quote! {},
+                "add" | "remove" =>
+                    match *query {
+                        Query::Relation { action: RelationAction::Add(ref expr), .. } |
+                            Query::Relation { action: RelationAction::Remove(ref expr), .. } =>
+                            expr_to_args(expr, &mut dummy_count, &mut count, &mut args),
+                        _ => quote! {},
+                    },
                 "aggregate" =>
                     if let Query::Aggregate { ref aggregates, .. } = *query {
                         aggregates_to_args(aggregates)
@@ -41,10 +60,24 @@ pub fn generate_macro_patterns(query: &Query, calls: &MethodCalls) -> TokenStrea
                     else {
                         quote! {}
                     },
-                "filter" | "get" =>
+                "get" =>
+                    match *query {
+                        Query::Aggregate { ref filter, .. } | Query::Delete { ref filter, .. } |
+                            Query::Relation { ref filter, .. } | Query::Select { ref filter, .. } |
+                            Query::Update { ref filter, .. } =>
+                            if let Some(args) = primary_key_to_args(filter, &mut dummy_count, &mut count, &mut args) {
+                                quote! { ( #args ) }
+                            }
+                            else {
+                                filter_to_args(filter, &mut dummy_count, &mut count, &mut args)
+                            },
+                        _ => quote! {},
+                    },
+                "filter" =>
                     match *query {
                         Query::Aggregate { ref filter, .. } | Query::Delete { ref filter, .. } |
-                            Query::Select { ref filter, .. } | Query::Update { ref filter, .. } =>
+                            Query::Relation { ref filter, .. } | Query::Select { ref filter, .. } |
+                            Query::Update { ref filter, .. } =>
                             filter_to_args(filter, &mut dummy_count, &mut count, &mut args),
                         _ => quote! {},
                     },
@@ -107,7 +140,7 @@ pub fn generate_macro_patterns(query: &Query, calls: &MethodCalls) -> TokenStrea
     quote! {
         #[allow(unused)]
         macro_rules! __tql_extract_exprs {
-            (#table_name #methods) => {
+            (#table_name #generic_arguments #methods) => {
                 #args
             };
         }
@@ -152,6 +185,22 @@ fn filter_to_args(filter: &FilterExpression, dummy_count: &mut i32, count: &mut
     }
 }
 
+/// Convert the filter of a `get()` with a tuple to the list of the values of the primary key columns.
+fn primary_key_to_args(filter: &FilterExpression, dummy_count: &mut i32, count: &mut i32, args: &mut Vec<Ident>) -> Option<TokenStream> {
+    match *filter {
+        FilterExpression::Filter(Filter { operand1: FilterValue::PrimaryKey(_, Some(_)), ref operand2, .. }) =>
+            Some(expr_to_args(operand2, dummy_count, count, args)),
+        FilterExpression::Filters(ref filters) => {
+            let left = primary_key_to_args(&filters.operand1, dummy_count, count, args)?;
+            let right = primary_key_to_args(&filters.operand2, dummy_count, count, args)?;
+            Some(quote! {
+                #left, #right
+            })
+        },
+        _ => None,
+    }
+}
+
 fn filter_value_to_args(filter_value: &FilterValue) -> TokenStream {
     match *filter_value {
         FilterValue::Identifier(_, ref identifier) => {
@@ -161,7 +210,10 @@ fn filter_value_to_args(filter_value: &FilterValue) -> TokenStream {
             #object_name . #method_name ( #(#arguments),* )
         },
         FilterValue::None => unreachable!(),
-        FilterValue::PrimaryKey(_) => quote! { },
+        FilterValue::PrimaryKey(..) => quote! { },
+        FilterValue::RelatedField(_, ref relation, ref identifier) => quote! {
+            #relation . #identifier
+        },
     }
 }
 
@@ -49,8 +49,8 @@ pub struct BothTypes {
 /// A collection of fields from an `SqlTable`.
 pub type SqlFields = BTreeMap<Ident, BothTypes>;
 
-/// A collection mapping method names to methods.
-pub type SqlMethods = HashMap<String, SqlMethodTypes>;
+/// A collection mapping method names to methods, which can exist on multiple object types.
+pub type SqlMethods = HashMap<String, Vec<SqlMethodTypes>>;
 
 /// Tql method return type, argument types and template.
 pub struct SqlMethodTypes {
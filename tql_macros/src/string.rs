@@ -19,7 +19,7 @@
  * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 
-//! String proximity lookup function.
+//! String proximity lookup and naming functions.
 
 use std::cmp;
 
@@ -98,6 +98,39 @@ pub fn plural_verb<'a>(count: usize) -> &'a str {
     }
 }
 
+/// Convert a CamelCase identifier to snake_case.
+pub fn to_snake_case(string: &str) -> String {
+    let chars: Vec<char> = string.chars().collect();
+    let mut result = String::new();
+    for (index, &character) in chars.iter().enumerate() {
+        if character.is_uppercase() && index > 0 && chars[index - 1] != '_' {
+            let previous_lowercase = !chars[index - 1].is_uppercase();
+            let next_lowercase = index + 1 < chars.len() && chars[index + 1].is_lowercase();
+            if previous_lowercase || next_lowercase {
+                result.push('_');
+            }
+        }
+        result.extend(character.to_lowercase());
+    }
+    result
+}
+
+/// Returns the English plural of the noun at the end of `string`.
+pub fn pluralize(string: &str) -> String {
+    let lowercase = string.to_lowercase();
+    if lowercase.ends_with('s') || lowercase.ends_with('x') || lowercase.ends_with('z') ||
+        lowercase.ends_with("ch") || lowercase.ends_with("sh")
+    {
+        format!("{}es", string)
+    }
+    else if lowercase.ends_with('y') && !lowercase[..lowercase.len() - 1].ends_with(|c| "aeiou".contains(c)) {
+        format!("{}ies", &string[..string.len() - 1])
+    }
+    else {
+        format!("{}s", string)
+    }
+}
+
 /// Convert a syn object to a string.
 pub fn token_to_string<T: ToTokens>(token: &T) -> String {
     (quote! { #token }).to_string()
@@ -21,7 +21,6 @@
 
 use std::fmt::{self, Display, Formatter};
 
-use proc_macro2::TokenStream;
 use syn::{
     self,
     AngleBracketedGenericArguments,
@@ -33,19 +32,26 @@ use syn::{
     Lit,
     Path,
     PathArguments,
+    PathSegment,
     TypePath,
 };
+use syn::punctuated::Punctuated;
+use syn::token::Colon2;
 
 use ast::Expression;
-use sql;
 
 /// A field type.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Type {
+    /// A `Vec<T>` stored in a PostgreSQL array.
+    Array(Box<Type>),
     Bool,
     ByteString,
     Char,
     Custom(String),
+    Decimal,
+    /// A `chrono::Duration`.
+    Duration,
     F32,
     F64,
     Generic,
@@ -53,25 +59,40 @@ pub enum Type {
     I16,
     I32,
     I64,
+    /// A `std::net::IpAddr`.
+    IpAddr,
+    /// A `Json<T>` or a `serde_json::Value`.
+    Json,
     LocalDateTime,
+    ManyToMany(String),
     NaiveDate,
     NaiveDateTime,
     NaiveTime,
     Nullable(Box<Type>),
-    Serial,
+    PrimaryKey(Box<Type>),
     String,
+    /// An unsigned integer stored in the next wider signed type.
+    U16,
+    U32,
+    U64,
     UnsupportedType(String),
+    /// A user-defined type implementing the `SqlType` trait.
+    UserDefined(String),
     UtcDateTime,
+    Uuid,
 }
 
 impl Display for Type {
     /// Get a string representation of the SQL `Type` for display in error messages.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let typ = match *self {
+            Type::Array(ref typ) => "Vec<".to_string() + &typ.to_string() + ">",
             Type::Bool => "bool".to_string(),
             Type::ByteString => "Vec<u8>".to_string(),
             Type::Char => "char".to_string(),
             Type::Custom(ref typ) => typ.clone(),
+            Type::Decimal => "rust_decimal::Decimal".to_string(),
+            Type::Duration => "chrono::Duration".to_string(),
             Type::F32 => "f32".to_string(),
             Type::F64 => "f64".to_string(),
             Type::Generic => "".to_string(),
@@ -79,32 +100,54 @@ impl Display for Type {
             Type::I16 => "i16".to_string(),
             Type::I32 => "i32".to_string(),
             Type::I64 => "i64".to_string(),
+            Type::IpAddr => "std::net::IpAddr".to_string(),
+            Type::Json => "Json".to_string(),
             Type::LocalDateTime => "chrono::datetime::DateTime<chrono::offset::Local>".to_string(),
+            Type::ManyToMany(ref typ) => "ManyToMany<".to_string() + typ + ">",
             Type::NaiveDate => "chrono::naive::NaiveDate".to_string(),
             Type::NaiveDateTime => "chrono::naive::NaiveDateTime".to_string(),
             Type::NaiveTime => "chrono::naive::NaiveTime".to_string(),
             Type::Nullable(ref typ) => "Option<".to_string() + &typ.to_string() + ">",
-            Type::Serial => "i32".to_string(),
+            Type::PrimaryKey(ref typ) => typ.to_string(),
             Type::String => "String".to_string(),
+            Type::U16 => "u16".to_string(),
+            Type::U32 => "u32".to_string(),
+            Type::U64 => "u64".to_string(),
             Type::UnsupportedType(_) => "".to_string(),
+            Type::UserDefined(ref typ) => typ.clone(),
             Type::UtcDateTime => "chrono::datetime::DateTime<chrono::offset::Utc>".to_string(),
+            Type::Uuid => "uuid::Uuid".to_string(),
         };
         write!(f, "{}", typ)
     }
 }
 
-/// Convert a `Type` to its SQL representation.
-pub fn type_to_sql(typ: &Type) -> TokenStream {
-    sql::type_to_sql(typ, false)
+impl Type {
+    /// Check if the value of this primary key type is generated when it is not inserted:
+    /// by the database for an integer and by tql for a `Uuid`.
+    pub fn is_generated(&self) -> bool {
+        match *self {
+            Type::PrimaryKey(ref typ) => **typ == Type::I32 || **typ == Type::I64 || **typ == Type::Uuid,
+            _ => false,
+        }
+    }
+
+    /// Check if this type can be the type of the elements of an array.
+    fn is_array_element(&self) -> bool {
+        matches!(*self, Type::Bool | Type::Decimal | Type::F32 | Type::F64 | Type::I16 | Type::I32 | Type::I64 |
+                 Type::LocalDateTime | Type::NaiveDate | Type::NaiveDateTime | Type::NaiveTime | Type::String |
+                 Type::UtcDateTime | Type::Uuid)
+    }
 }
 
 impl PartialEq<Expression> for Type {
     /// Check if an literal `expression` is equal to a `Type`.
     fn eq(&self, expression: &Expression) -> bool {
         // If the field type is `Nullable`, `expected_type` needs not to be an `Option`.
+        // Similarly, the primary key is compared as its inner type.
         let typ =
             match *self {
-                Type::Nullable(ref typ) => typ,
+                Type::Nullable(ref typ) | Type::PrimaryKey(ref typ) => typ,
                 ref typ => typ,
             };
         match *expression {
@@ -124,16 +167,20 @@ impl PartialEq<Expression> for Type {
                     IntSuffix::Isize => false,
                     IntSuffix::I8 => *typ == Type::I8,
                     IntSuffix::I16 => *typ == Type::I16,
-                    IntSuffix::I32 => *typ == Type::I32 || *typ == Type::Serial,
+                    IntSuffix::I32 => *typ == Type::I32,
                     IntSuffix::I64 => *typ == Type::I64,
-                    IntSuffix::U8 | IntSuffix::U16 | IntSuffix::U32 | IntSuffix::U64 | IntSuffix::U128 |
-                        IntSuffix::Usize | IntSuffix::I128 => false,
+                    IntSuffix::U16 => *typ == Type::U16,
+                    IntSuffix::U32 => *typ == Type::U32,
+                    IntSuffix::U64 => *typ == Type::U64,
+                    IntSuffix::U8 | IntSuffix::U128 | IntSuffix::Usize | IntSuffix::I128 => false,
                     IntSuffix::None =>
                         *typ == Type::I8 ||
                         *typ == Type::I16 ||
                         *typ == Type::I32 ||
                         *typ == Type::I64 ||
-                        *typ == Type::Serial,
+                        *typ == Type::U16 ||
+                        *typ == Type::U32 ||
+                        *typ == Type::U64,
                 },
             Expr::Lit(ExprLit { lit: Lit::Str(_), .. }) => *typ == Type::String,
             _ => true, // Returns true, because the type checking for non-literal is done later.
@@ -152,6 +199,11 @@ impl<'a> From<&'a Path> for Type {
             match &ident[..] {
                 "bool" => Type::Bool,
                 "char" => Type::Char,
+                // NOTE: a Cow<str> is converted to a String.
+                "Cow" => match get_type_parameter(&first_segment.arguments) {
+                    Some(ref ty) if ty == "str" => Type::String,
+                    _ => Type::UnsupportedType("Cow".to_string()),
+                },
                 "DateTime" => match get_type_parameter(&first_segment.arguments) {
                     Some(ty) => match ty.as_ref() {
                         "Local" => Type::LocalDateTime,
@@ -166,10 +218,18 @@ impl<'a> From<&'a Path> for Type {
                 "i16" => Type::I16,
                 "i32" => Type::I32,
                 "i64" => Type::I64,
+                "IpAddr" => Type::IpAddr,
+                // NOTE: the type parameter can be any type implementing the serde traits.
+                "Json" if !first_segment.arguments.is_empty() => Type::Json,
+                "Json" => Type::UnsupportedType("Json".to_string()),
                 "ForeignKey" => match get_type_parameter(&first_segment.arguments) {
                     Some(ty) => Type::Custom(ty),
                     None => Type::UnsupportedType("ForeignKey".to_string()),
                 },
+                "ManyToMany" => match get_type_parameter(&first_segment.arguments) {
+                    Some(ty) => Type::ManyToMany(ty),
+                    None => Type::UnsupportedType("ManyToMany".to_string()),
+                },
                 "NaiveDate" => Type::NaiveDate,
                 "NaiveDateTime" => Type::NaiveDateTime,
                 "NaiveTime" => Type::NaiveTime,
@@ -188,40 +248,68 @@ impl<'a> From<&'a Path> for Type {
                         },
                         None => Type::UnsupportedType("Option".to_string()),
                     },
-                "PrimaryKey" => {
-                    Type::Serial
-                },
+                "PrimaryKey" =>
+                    match get_type_parameter_as_path(&first_segment.arguments) {
+                        Some(ty) => Type::PrimaryKey(Box::new(From::from(ty))),
+                        None => Type::PrimaryKey(Box::new(Type::I32)),
+                    },
                 "String" => {
                     Type::String
                 },
-                "Vec" => match get_type_parameter(&first_segment.arguments) {
-                    Some(ty) => match ty.as_ref() {
-                        "u8" => Type::ByteString,
-                        parameter_type => Type::UnsupportedType("Vec<".to_string() + parameter_type + ">"),
+                "u16" => Type::U16,
+                "u32" => Type::U32,
+                "u64" => Type::U64,
+                "Uuid" => Type::Uuid,
+                "Vec" => match get_type_parameter_as_path(&first_segment.arguments) {
+                    Some(ty) => match Type::from(ty) {
+                        Type::UnsupportedType(ref typ) if typ == "u8" => Type::ByteString,
+                        typ if typ.is_array_element() => Type::Array(Box::new(typ)),
+                        _ => Type::UnsupportedType(format!("Vec<{}>", path_idents(&ty.segments).join("::"))),
                     },
                     None => Type::UnsupportedType("Vec".to_string()),
                 },
+                "i128" | "isize" | "str" | "u8" | "u128" | "usize" =>
+                    Type::UnsupportedType(ident.clone()),
+                // NOTE: any other type without generic parameters must implement the SqlType trait.
+                typ if first_segment.arguments.is_empty() => Type::UserDefined(typ.to_string()),
                 typ => Type::UnsupportedType(typ.to_string()), // TODO: show the generic types as well.
             }
         }
         else {
-            unsupported
+            let idents = path_idents(segments);
+            let idents: Vec<_> = idents.iter().map(String::as_str).collect();
+            match &idents[..] {
+                ["chrono", "Duration"] => Type::Duration,
+                ["rust_decimal", "Decimal"] => Type::Decimal,
+                ["std", "net", "IpAddr"] => Type::IpAddr,
+                ["serde_json", "Value"] => Type::Json,
+                ["uuid", "Uuid"] => Type::Uuid,
+                _ => unsupported,
+            }
         }
     }
 }
 
+/// Get the identifiers of a path without generic arguments.
+fn path_idents(segments: &Punctuated<PathSegment, Colon2>) -> Vec<String> {
+    segments.iter()
+        .map(|segment| segment.ident.to_string())
+        .collect()
+}
+
 /// Get the type between < and > as a String.
 pub fn get_type_parameter(parameters: &PathArguments) -> Option<String> {
     get_type_parameter_as_path(parameters).map(|path| path.segments.first()
         .expect("first segment in path").value().ident.to_string())
 }
 
-/// Get the type between < and > as a Path.
+/// Get the type between < and > as a Path, ignoring the lifetimes.
 pub fn get_type_parameter_as_path(parameters: &PathArguments) -> Option<&Path> {
     if let PathArguments::AngleBracketed(AngleBracketedGenericArguments { ref args, .. }) = *parameters {
-        args.first()
+        args.iter()
+            .find(|arg| !matches!(**arg, GenericArgument::Lifetime(_)))
             .and_then(|ty| {
-                if let GenericArgument::Type(syn::Type::Path(TypePath { ref path, .. })) = **ty.value() {
+                if let GenericArgument::Type(syn::Type::Path(TypePath { ref path, .. })) = *ty {
                     Some(path)
                 }
                 else {